@@ -4,4 +4,4 @@ set -eux -o pipefail
 
 cd "$(dirname "$0")/src/"
 rm -- *.rs
-sea-orm-cli generate entity -l
+sea-orm-cli generate entity -l --with-serde serialize
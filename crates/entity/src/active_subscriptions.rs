@@ -1,13 +1,17 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
 
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize)]
 #[sea_orm(table_name = "active_subscriptions")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
     pub channel_id: String,
     pub expiration: entity_types::jiff_compat::JiffTimestampMilliseconds,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(skip_serializing)]
+    pub secret: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,8 +1,9 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
 
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "known_channels")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false, column_type = "Text")]
@@ -11,12 +12,29 @@ pub struct Model {
     pub channel_name: String,
     #[sea_orm(column_type = "Text")]
     pub channel_profile_picture: String,
+    pub is_shorts_channel: bool,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub video_age_threshold_minutes: Option<f64>,
+    pub last_video_at: Option<entity_types::jiff_compat::JiffTimestampMilliseconds>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub handle: Option<String>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub shorts_ratio: Option<f64>,
+    pub shorts_ratio_samples: i32,
+    pub notifications_paused: bool,
+    pub subscription_count: Option<i32>,
+    pub subscriber_count: Option<i64>,
+    pub priority: i32,
+    pub metadata_updated_at: Option<entity_types::jiff_compat::JiffTimestampMilliseconds>,
+    pub profile_picture_updated_at: Option<entity_types::jiff_compat::JiffTimestampMilliseconds>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_one = "super::active_subscriptions::Entity")]
     ActiveSubscriptions,
+    #[sea_orm(has_many = "super::channel_group_members::Entity")]
+    ChannelGroupMembers,
     #[sea_orm(has_many = "super::subscription_queue::Entity")]
     SubscriptionQueue,
     #[sea_orm(has_many = "super::video_queue::Entity")]
@@ -29,6 +47,12 @@ impl Related<super::active_subscriptions::Entity> for Entity {
     }
 }
 
+impl Related<super::channel_group_members::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChannelGroupMembers.def()
+    }
+}
+
 impl Related<super::subscription_queue::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::SubscriptionQueue.def()
@@ -41,4 +65,17 @@ impl Related<super::video_queue::Entity> for Entity {
     }
 }
 
+impl Related<super::channel_groups::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::channel_group_members::Relation::ChannelGroups.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(
+            super::channel_group_members::Relation::KnownChannels
+                .def()
+                .rev(),
+        )
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -5,12 +5,23 @@ use sea_orm::{Linked, RelationDef, RelationTrait as _};
 pub mod prelude;
 
 pub mod active_subscriptions;
+pub mod actor_runs;
+pub mod audit_log;
+pub mod channel_group_members;
+pub mod channel_groups;
+pub mod failed_feed_parses;
 pub mod known_channels;
+pub mod maintenance_windows;
 pub mod o_auth;
+pub mod shorts_detection_cache;
 pub mod subscription_queue;
 pub mod subscription_queue_result;
+pub mod subscription_refresh_state;
+pub mod subscription_sync_runs;
+pub mod video_blacklist;
 pub mod video_queue;
 pub mod video_queue_result;
+pub mod youtube_api_calls;
 
 pub struct SubscriptionQueueToActiveSubscriptions;
 
@@ -1,9 +1,20 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
 
 pub use super::active_subscriptions::Entity as ActiveSubscriptions;
+pub use super::actor_runs::Entity as ActorRuns;
+pub use super::audit_log::Entity as AuditLog;
+pub use super::channel_group_members::Entity as ChannelGroupMembers;
+pub use super::channel_groups::Entity as ChannelGroups;
+pub use super::failed_feed_parses::Entity as FailedFeedParses;
 pub use super::known_channels::Entity as KnownChannels;
+pub use super::maintenance_windows::Entity as MaintenanceWindows;
 pub use super::o_auth::Entity as OAuth;
+pub use super::shorts_detection_cache::Entity as ShortsDetectionCache;
 pub use super::subscription_queue::Entity as SubscriptionQueue;
 pub use super::subscription_queue_result::Entity as SubscriptionQueueResult;
+pub use super::subscription_refresh_state::Entity as SubscriptionRefreshState;
+pub use super::subscription_sync_runs::Entity as SubscriptionSyncRuns;
+pub use super::video_blacklist::Entity as VideoBlacklist;
 pub use super::video_queue::Entity as VideoQueue;
 pub use super::video_queue_result::Entity as VideoQueueResult;
+pub use super::youtube_api_calls::Entity as YoutubeApiCalls;
@@ -1,8 +1,9 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
 
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize)]
 #[sea_orm(table_name = "subscription_queue")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -11,6 +12,15 @@ pub struct Model {
     pub channel_id: String,
     pub action: entity_types::subscription_queue::SubscriptionAction,
     pub timestamp: entity_types::jiff_compat::JiffTimestampMilliseconds,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(skip_serializing)]
+    pub secret: Option<String>,
+    pub scheduled_for: Option<entity_types::jiff_compat::JiffTimestampMilliseconds>,
+    pub priority: i32,
+    pub source: entity_types::subscription_queue::SubscriptionSource,
+    pub retry_count: i32,
+    pub sent_at: Option<entity_types::jiff_compat::JiffTimestampMilliseconds>,
+    pub confirmed_at: Option<entity_types::jiff_compat::JiffTimestampMilliseconds>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,8 +1,9 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
 
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize)]
 #[sea_orm(table_name = "video_queue")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -16,6 +17,15 @@ pub struct Model {
     pub published_at: entity_types::jiff_compat::JiffTimestampMilliseconds,
     pub updated_at: entity_types::jiff_compat::JiffTimestampMilliseconds,
     pub timestamp: entity_types::jiff_compat::JiffTimestampMilliseconds,
+    #[sea_orm(column_type = "Text")]
+    pub channel_name: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub sender_ip: Option<String>,
+    pub processing_since: Option<entity_types::jiff_compat::JiffTimestampMilliseconds>,
+    pub priority: i32,
+    pub is_short_hint: Option<bool>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub target_playlist_id: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,19 +1,26 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
 
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize)]
 #[sea_orm(table_name = "video_queue_result")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub queue_id: i32,
-    #[sea_orm(column_type = "Text")]
-    pub action: String,
+    pub action: entity_types::video_queue::VideoAction,
     pub shorts_redirect: bool,
     #[sea_orm(column_type = "Text")]
     pub visibility: String,
     pub duration: entity_types::jiff_compat::JiffSignedDurationSeconds,
     pub timestamp: entity_types::jiff_compat::JiffTimestampMilliseconds,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub playlist_id: Option<String>,
+    pub shorts_detection_method: Option<entity_types::video_queue::ShortsDetectionMethod>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub playlist_item_id: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error_message: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
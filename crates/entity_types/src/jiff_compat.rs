@@ -2,10 +2,12 @@ use sea_orm::{
     ColumnType, TryGetable, Value,
     sea_query::{ArrayType, Nullable, ValueType, ValueTypeErr},
 };
+use serde::Serialize;
 
 /// Storage type for a [`jiff::Timestamp`] which will store the timesamp as an
 /// BIGINTEGER representing miliseconds since the UNIX epoch
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
 pub struct JiffTimestampMilliseconds(pub jiff::Timestamp);
 
 impl From<JiffTimestampMilliseconds> for Value {
@@ -64,7 +66,8 @@ impl TryGetable for JiffTimestampMilliseconds {
 
 /// Storage type for a [`jiff::SignedDuration`] which will store the duration as an
 /// BIGINTEGER representing the duration in seconds
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
 pub struct JiffSignedDurationSeconds(pub jiff::SignedDuration);
 
 impl From<JiffSignedDurationSeconds> for Value {
@@ -1,2 +1,5 @@
 pub mod jiff_compat;
-pub mod subscription_queue;
\ No newline at end of file
+pub mod subscription_queue;
+pub mod video_queue;
+
+pub use jiff_compat::{JiffSignedDurationSeconds, JiffTimestampMilliseconds};
@@ -1,7 +1,9 @@
 use sea_orm::{DeriveActiveEnum, EnumIter};
+use serde::Serialize;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize)]
 #[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
 pub enum SubscriptionAction {
     #[sea_orm(string_value = "subscribe")]
     Subscribe,
@@ -9,4 +11,37 @@ pub enum SubscriptionAction {
     Unsubscribe,
     #[sea_orm(string_value = "refresh")]
     Refresh,
+    /// Subscribe with a newly generated secret, even if the channel already has one
+    #[sea_orm(string_value = "force_subscribe")]
+    ForceSubscribe,
+    /// Stop queueing new uploads from the channel, without touching the hub subscription
+    #[sea_orm(string_value = "pause_notifications")]
+    PauseNotifications,
+    #[sea_orm(string_value = "resume_notifications")]
+    ResumeNotifications,
+}
+
+/// Why an action was queued
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionSource {
+    /// Queued before sources were recorded
+    #[sea_orm(string_value = "unknown")]
+    Unknown,
+    /// Hourly sync with the youtube subscriptions of the user
+    #[sea_orm(string_value = "hourly")]
+    Hourly,
+    /// The hub subscription is about to expire
+    #[sea_orm(string_value = "expiration_refresh")]
+    ExpirationRefresh,
+    /// Requested through the admin api
+    #[sea_orm(string_value = "admin_manual")]
+    AdminManual,
+    /// The channel was imported through the admin api
+    #[sea_orm(string_value = "new_channel_added")]
+    NewChannelAdded,
+    /// Retry of an action that the hub did not accept
+    #[sea_orm(string_value = "verification_failed")]
+    VerificationFailed,
 }
@@ -0,0 +1,55 @@
+use sea_orm::{DeriveActiveEnum, EnumIter};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum VideoAction {
+    #[sea_orm(string_value = "inserted")]
+    Inserted,
+    #[sea_orm(string_value = "skipped_old")]
+    SkippedOld,
+    #[sea_orm(string_value = "skipped_short")]
+    SkippedShort,
+    #[sea_orm(string_value = "skipped_duplicate")]
+    SkippedDuplicate,
+    #[sea_orm(string_value = "skipped_unknown_channel")]
+    SkippedUnknownChannel,
+    #[sea_orm(string_value = "skipped_not_public")]
+    SkippedNotPublic,
+    #[sea_orm(string_value = "failed")]
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionMethod {
+    #[sea_orm(string_value = "redirect")]
+    Redirect,
+}
+
+/// What decided whether a processed video was a short
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "snake_case")]
+pub enum ShortsDetectionMethod {
+    /// The youtube shorts url did not redirect to the watch page
+    #[sea_orm(string_value = "redirect")]
+    Redirect,
+    /// The video could not be checked, but most of the channel's recent videos are shorts
+    #[sea_orm(string_value = "heuristic")]
+    Heuristic,
+    /// The video was already checked
+    #[sea_orm(string_value = "cached")]
+    Cached,
+    /// The channel is set to only post shorts
+    #[sea_orm(string_value = "channel_flag")]
+    ChannelFlag,
+    /// The video could not be checked, but the thumbnail in the feed notification was portrait
+    #[sea_orm(string_value = "feed_hint")]
+    FeedHint,
+    /// Detection failed, so the video was assumed not to be a short
+    #[sea_orm(string_value = "none")]
+    None,
+}
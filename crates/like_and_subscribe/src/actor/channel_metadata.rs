@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use jiff::{SignedDuration, Timestamp};
+use sea_orm::{DatabaseConnection, DbErr};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    actor::subscription::{get_channels, profile_picture},
+    database::KnownChannels,
+    oauth::TokenManager,
+    task_metrics::idle,
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24);
+/// Channels are refreshed once their metadata is older than this
+const METADATA_MAX_AGE: SignedDuration = SignedDuration::from_hours(24 * 30);
+
+/// Refresh the names and thumbnails of channels that are not covered by the hourly
+/// subscription sync, such as imported channels
+#[tracing::instrument(skip_all)]
+pub async fn channel_metadata_refresh(
+    shutdown: CancellationToken,
+    database: DatabaseConnection,
+    client: reqwest::Client,
+    token_manager: TokenManager,
+) -> Result<(), DbErr> {
+    let mut refresh_interval = tokio::time::interval(REFRESH_INTERVAL);
+    refresh_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = idle(refresh_interval.tick()) => {},
+        }
+
+        let channel_ids = KnownChannels::get_stale_metadata_channel_ids(
+            &database,
+            Timestamp::now() - METADATA_MAX_AGE,
+        )
+        .await
+        .inspect_err(
+            |error| tracing::error!(%error, "failed to get channels with stale metadata"),
+        )?;
+
+        if channel_ids.is_empty() {
+            tracing::debug!("no channels with stale metadata");
+            continue;
+        }
+
+        let token = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            token_result = token_manager.wait_for_token() => token_result.inspect_err(|error| tracing::error!(%error, "failed to get current token"))?,
+        };
+
+        // Failures are logged, try again on the next refresh
+        let channels = match get_channels(&database, &client, &token, &channel_ids).await {
+            Ok(channels) => channels,
+            Err(error) => {
+                tracing::warn!(%error, "failed to fetch channel details");
+                continue;
+            }
+        };
+
+        for (channel_id, channel) in &channels {
+            let name = channel
+                .snippet
+                .as_ref()
+                .and_then(|snippet| snippet.title.as_deref());
+            let picture = profile_picture(channel);
+
+            KnownChannels::update_channel_metadata(&database, channel_id, name, picture.as_deref())
+                .await
+                .inspect_err(
+                    |error| tracing::error!(%error, "failed to update channel metadata"),
+                )?;
+        }
+
+        tracing::info!(
+            stale = channel_ids.len(),
+            refreshed = channels.len(),
+            "refreshed channel metadata"
+        );
+    }
+
+    tracing::info!("shutting down");
+
+    Ok(())
+}
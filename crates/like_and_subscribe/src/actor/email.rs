@@ -1,25 +1,38 @@
+use std::time::Duration;
+
 use mail_send::{
     Credentials, SmtpClientBuilder,
     mail_builder::{MessageBuilder, headers::address::Address},
+    smtp::message::IntoMessage as _,
 };
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
+use crate::task_metrics::idle;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[tracing::instrument(skip(shutdown, email_credentials, email_send_rx))]
 pub async fn email_sender(
     shutdown: CancellationToken,
+    smtp_host: String,
+    smtp_port: u16,
     email_credentials: Credentials<String>,
+    reconnect_attempts: u32,
     mut email_send_rx: mpsc::Receiver<MessageBuilder<'static>>,
 ) -> Result<(), ()> {
-    let mut smtp = SmtpClientBuilder::new("smtp.fastmail.com".to_string(), 465)
-        .credentials(email_credentials)
-        .connect()
-        .await
-        .unwrap();
+    let smtp_builder = SmtpClientBuilder::new(smtp_host, smtp_port).credentials(email_credentials);
+    let mut smtp = smtp_builder.connect().await.unwrap();
 
     loop {
-        let email = tokio::select! {
-            _ = shutdown.cancelled() => break,
-            email = email_send_rx.recv() => {email}
+        let email = if shutdown.is_cancelled() {
+            // Deliver any alerts that were queued before shutting down
+            email_send_rx.try_recv().ok()
+        } else {
+            tokio::select! {
+                _ = shutdown.cancelled() => continue,
+                email = idle(email_send_rx.recv()) => {email}
+            }
         };
 
         let Some(email) = email else {
@@ -33,9 +46,36 @@ pub async fn email_sender(
                 "me@dusterthefirst.com",
             ));
 
-        // FIXME: do we need to reconnect to the smtp server each time?
-        if let Err(error) = smtp.send(email).await {
-            tracing::error!(%error, "failed to send email");
+        let message = match email.into_message() {
+            Ok(message) => message,
+            Err(error) => {
+                tracing::error!(%error, "failed to build email");
+                continue;
+            }
+        };
+
+        let mut result = smtp.send(message.clone()).await;
+
+        for attempt in 1..=reconnect_attempts {
+            let Err(error) = &result else {
+                break;
+            };
+
+            tracing::warn!(%error, attempt, "failed to send email, reconnecting to the smtp server");
+
+            tokio::time::sleep(RECONNECT_DELAY).await;
+
+            result = match smtp_builder.connect().await {
+                Ok(new_smtp) => {
+                    smtp = new_smtp;
+                    smtp.send(message.clone()).await
+                }
+                Err(error) => Err(error),
+            };
+        }
+
+        if let Err(error) = result {
+            tracing::error!(%error, "failed to send email, discarding");
         } else {
             tracing::info!("sent alert email");
         }
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use jiff::{SignedDuration, Timestamp};
+use sea_orm::{DatabaseConnection, DbErr};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    actor::next_run_delay,
+    alert::{AlertKind, AlertSender},
+    database::{ActorRuns, KnownChannels},
+    task_metrics::idle,
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+/// Name the last run is recorded under
+const ACTOR_NAME: &str = "inactive_channels";
+
+pub async fn inactive_channel_check(
+    shutdown: CancellationToken,
+    database: DatabaseConnection,
+    alerts: AlertSender,
+    inactive_threshold: SignedDuration,
+) -> Result<(), DbErr> {
+    // Continue the schedule from the last run, so that frequent restarts do not postpone it forever
+    let last_run = ActorRuns::get_last_run(&database, ACTOR_NAME)
+        .await
+        .inspect_err(
+            |error| tracing::error!(%error, "failed to get last inactive channel check"),
+        )?;
+
+    let mut check_interval = tokio::time::interval_at(
+        Instant::now() + next_run_delay(last_run, CHECK_INTERVAL),
+        CHECK_INTERVAL,
+    );
+    check_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = idle(check_interval.tick()) => {},
+        }
+
+        let channels =
+            KnownChannels::get_inactive(&database, Timestamp::now() - inactive_threshold)
+                .await
+                .inspect_err(|error| tracing::error!(%error, "failed to get inactive channels"))?;
+
+        if channels.is_empty() {
+            tracing::debug!("no inactive channels");
+        } else {
+            tracing::info!(count = channels.len(), "found inactive channels");
+
+            alerts
+                .send_alert(AlertKind::InactiveChannels {
+                    channels,
+                    days: inactive_threshold.as_hours() / 24,
+                })
+                .await;
+        }
+
+        ActorRuns::record_run(&database, ACTOR_NAME)
+            .await
+            .inspect_err(
+                |error| tracing::error!(%error, "failed to record inactive channel check"),
+            )?;
+    }
+
+    tracing::info!("shutting down");
+
+    Ok(())
+}
@@ -1,4 +1,31 @@
+use std::time::Duration;
+
+use jiff::Timestamp;
+
+pub mod channel_metadata;
 pub mod email;
+pub mod inactive_channels;
+pub mod playlist;
+pub mod pool_stats;
 pub mod pubsubhubbub;
 pub mod subscription;
+pub mod task_metrics;
+pub mod video;
+pub mod video_queue_cleanup;
 pub mod web;
+
+/// Time left until the next run of an actor that runs every `period`, or [`Duration::ZERO`] if
+/// it has never run or is overdue
+pub fn next_run_delay(last_run: Option<Timestamp>, period: Duration) -> Duration {
+    let Some(last_run) = last_run else {
+        return Duration::ZERO;
+    };
+
+    // A last run in the future, from the clock going backwards, counts as just now
+    let elapsed = last_run
+        .duration_until(Timestamp::now())
+        .try_into()
+        .unwrap_or(Duration::ZERO);
+
+    period.saturating_sub(elapsed)
+}
@@ -0,0 +1,218 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use oauth2::AccessToken;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Serialize;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    actor::next_run_delay,
+    alert::{AlertKind, AlertSender},
+    config::Config,
+    database::{ActorRuns, ShortsDetectionCache, VideoQueue},
+    oauth::TokenManager,
+    playlist::{self, ApiError, PlaylistVideo},
+    task_metrics::idle,
+};
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{actor::playlist::PlaylistDiff, playlist::PlaylistVideo};
+
+    #[test]
+    fn diff_playlist() {
+        let videos = ["a", "b", "c"].map(|video_id| PlaylistVideo {
+            item_id: format!("item-{video_id}"),
+            video_id: video_id.to_owned(),
+        });
+        let inserted = HashSet::from(["b", "c", "d"].map(str::to_owned));
+
+        let diff = PlaylistDiff::new(&videos, &inserted);
+
+        assert_eq!(diff.in_playlist_not_db, ["a"]);
+        assert_eq!(diff.in_db_not_playlist, ["d"]);
+        assert_eq!(diff.matched, 2);
+    }
+}
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24);
+/// Name the last run is recorded under
+const ACTOR_NAME: &str = "playlist";
+
+/// Differences between the playlist and the videos that have been inserted into it
+#[derive(Debug, Serialize)]
+pub struct PlaylistDiff {
+    /// Added to the playlist by hand, or inserted before the database was created
+    pub in_playlist_not_db: Vec<String>,
+    /// Removed from the playlist by hand, or the insert was not actually applied
+    pub in_db_not_playlist: Vec<String>,
+    pub matched: usize,
+}
+
+impl PlaylistDiff {
+    pub fn new(videos: &[PlaylistVideo], inserted: &HashSet<String>) -> Self {
+        let in_playlist =
+            HashSet::<&str>::from_iter(videos.iter().map(|video| video.video_id.as_str()));
+
+        let in_playlist_not_db = Vec::from_iter(
+            videos
+                .iter()
+                .map(|video| video.video_id.clone())
+                .filter(|video_id| !inserted.contains(video_id)),
+        );
+
+        let mut in_db_not_playlist = Vec::from_iter(
+            inserted
+                .iter()
+                .filter(|video_id| !in_playlist.contains(video_id.as_str()))
+                .cloned(),
+        );
+        in_db_not_playlist.sort_unstable();
+
+        Self {
+            matched: videos.len() - in_playlist_not_db.len(),
+            in_playlist_not_db,
+            in_db_not_playlist,
+        }
+    }
+}
+
+/// Compare the playlist against the videos that have been inserted into it, logging any differences
+pub async fn playlist_manager(
+    shutdown: CancellationToken,
+    database: DatabaseConnection,
+    client: reqwest::Client,
+    token_manager: TokenManager,
+    config: Arc<Config>,
+    alerts: AlertSender,
+) -> Result<(), DbErr> {
+    // Continue the schedule from the last run, so that frequent restarts do not postpone it forever
+    let last_run = ActorRuns::get_last_run(&database, ACTOR_NAME)
+        .await
+        .inspect_err(|error| tracing::error!(%error, "failed to get last playlist check"))?;
+
+    let mut check_interval = tokio::time::interval_at(
+        Instant::now() + next_run_delay(last_run, CHECK_INTERVAL),
+        CHECK_INTERVAL,
+    );
+    check_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = idle(check_interval.tick()) => {},
+        }
+
+        let token = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            token_result = token_manager.wait_for_token() => token_result.inspect_err(|error| tracing::error!(%error, "failed to get current token"))?,
+        };
+
+        let shorts = ShortsDetectionCache::get_shorts(&database)
+            .await
+            .inspect_err(|error| tracing::error!(%error, "failed to get detected shorts"))?;
+
+        for playlist_id in config.playlist_ids() {
+            check_playlist(
+                &database,
+                &client,
+                &token,
+                &config,
+                &alerts,
+                &shorts,
+                playlist_id,
+            )
+            .await?;
+        }
+
+        ActorRuns::record_run(&database, ACTOR_NAME)
+            .await
+            .inspect_err(|error| tracing::error!(%error, "failed to record playlist check"))?;
+    }
+
+    tracing::info!("shutting down");
+
+    Ok(())
+}
+
+async fn check_playlist(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    token: &AccessToken,
+    config: &Config,
+    alerts: &AlertSender,
+    shorts: &HashSet<String>,
+    playlist_id: &str,
+) -> Result<(), DbErr> {
+    // Failures are logged, try again on the next check
+    let videos = match playlist::list_videos(database, client, token, playlist_id).await {
+        Ok(videos) => videos,
+        Err(error) => {
+            if let ApiError::QuotaExceeded = error {
+                alerts.send_alert(AlertKind::QuotaExceeded).await;
+            }
+
+            tracing::warn!(playlist_id, %error, "failed to list playlist videos");
+            return Ok(());
+        }
+    };
+
+    let inserted =
+        VideoQueue::get_inserted_video_ids(database, playlist_id, config.default_playlist_id())
+            .await
+            .inspect_err(|error| tracing::error!(%error, "failed to get inserted videos"))?;
+
+    let diff = PlaylistDiff::new(&videos, &inserted);
+
+    if !diff.in_db_not_playlist.is_empty() {
+        tracing::warn!(
+            playlist_id,
+            count = diff.in_db_not_playlist.len(),
+            missing = ?diff.in_db_not_playlist,
+            "inserted videos are missing from the playlist"
+        );
+    }
+    if !diff.in_playlist_not_db.is_empty() {
+        tracing::info!(
+            playlist_id,
+            count = diff.in_playlist_not_db.len(),
+            unexpected = ?diff.in_playlist_not_db,
+            "playlist contains videos that were not inserted"
+        );
+    }
+
+    for video in videos
+        .iter()
+        .filter(|video| shorts.contains(&video.video_id))
+    {
+        if !config.playlist_remove_shorts {
+            tracing::warn!(
+                playlist_id,
+                video_id = video.video_id,
+                "playlist contains a short"
+            );
+            continue;
+        }
+
+        match playlist::remove_video(database, client, token, video).await {
+            Ok(()) => tracing::info!(
+                playlist_id,
+                video_id = video.video_id,
+                "removed short from playlist"
+            ),
+            Err(error) => {
+                tracing::warn!(playlist_id, video_id = video.video_id, %error, "failed to remove short from playlist");
+
+                if let ApiError::QuotaExceeded = error {
+                    alerts.send_alert(AlertKind::QuotaExceeded).await;
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
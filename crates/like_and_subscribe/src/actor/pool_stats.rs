@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{pool_stats::PoolStats, task_metrics::idle};
+
+const LOG_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+pub async fn pool_stats_logger(shutdown: CancellationToken, stats: PoolStats) {
+    let mut previous = stats.snapshot();
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = idle(tokio::time::sleep(LOG_INTERVAL)) => {},
+        }
+
+        let current = stats.snapshot();
+
+        tracing::info!(
+            pending = current.pending,
+            opened = current.opened - previous.opened,
+            failed = current.failed - previous.failed,
+            total_opened = current.opened,
+            "http connection pool stats"
+        );
+
+        previous = current;
+    }
+
+    tracing::info!("shutting down");
+}
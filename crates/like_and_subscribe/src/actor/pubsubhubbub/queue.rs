@@ -1,14 +1,23 @@
-use std::sync::Arc;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use entity_types::subscription_queue::SubscriptionAction;
 use futures::{StreamExt, stream};
+use jiff::{SignedDuration, Timestamp};
 use reqwest::Client;
 use sea_orm::{DatabaseConnection, DbErr};
 use serde::{Deserialize, Serialize};
 use tokio::sync::Notify;
 use tokio_util::sync::CancellationToken;
 
-use crate::database::SubscriptionQueue;
+use crate::{
+    alert::{AlertKind, AlertSender},
+    config::Config,
+    database::{KnownChannels, SubscriptionQueue},
+    task_metrics::idle,
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
@@ -27,79 +36,214 @@ pub struct HubRequest<'s> {
     pub(crate) mode: Mode,
     #[serde(rename = "hub.verify")]
     pub(crate) verify: Verify,
+    #[serde(rename = "hub.secret", skip_serializing_if = "Option::is_none")]
+    pub(crate) secret: Option<&'s str>,
 }
 
+/// Whether the hub verifies the subscription before responding to the request, or afterwards
 #[derive(Debug, Serialize, Clone, Copy)]
 pub enum Verify {
-    #[serde(rename = "async")]
-    Asynchronous,
     #[serde(rename = "sync")]
     Synchronous,
+    #[serde(rename = "async")]
+    Asynchronous,
 }
 
 fn topic(channel_id: &str) -> String {
     format!("https://www.youtube.com/xml/feeds/videos.xml?channel_id={channel_id}")
 }
 
+#[derive(Debug)]
+enum ProcessError {
+    Request(reqwest::Error),
+    Database(DbErr),
+}
+
+impl std::fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessError::Request(error) => error.fmt(f),
+            ProcessError::Database(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProcessError::Request(error) => Some(error),
+            ProcessError::Database(error) => Some(error),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ProcessError {
+    fn from(error: reqwest::Error) -> Self {
+        ProcessError::Request(error)
+    }
+}
+
+impl From<DbErr> for ProcessError {
+    fn from(error: DbErr) -> Self {
+        ProcessError::Database(error)
+    }
+}
+
+/// Hub requests taking longer than this are cancelled so they do not hold up the rest of the queue
+const SUBSCRIPTION_ACTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[tracing::instrument(skip(shutdown, database, notify, client, config, alerts))]
 pub async fn pubsub_queue_consumer(
     shutdown: CancellationToken,
     database: DatabaseConnection,
     notify: Arc<Notify>,
     client: Client,
     callback: String,
+    config: Arc<Config>,
+    alerts: AlertSender,
 ) -> Result<(), DbErr> {
     loop {
-        let actions = SubscriptionQueue::get_pending_actions(&database)
-            .await
-            .inspect_err(
+        // Release the database connection straight away if shutting down
+        let actions = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            actions = SubscriptionQueue::get_pending_actions(&database, config.subscription_max_retries) => actions.inspect_err(
                 |error| tracing::error!(%error, "failed to get pending actions from database"),
-            )?;
+            )?,
+        };
+
+        match SubscriptionQueue::count_all_pending(&database, config.subscription_max_retries).await
+        {
+            Ok(pending)
+                if SubscriptionQueue::is_nearly_full(
+                    pending,
+                    config.subscription_queue_max_rows,
+                ) =>
+            {
+                alerts
+                    .send_alert(AlertKind::SubscriptionQueueNearlyFull {
+                        pending,
+                        max_rows: config.subscription_queue_max_rows,
+                    })
+                    .await;
+            }
+            Ok(_) => {}
+            Err(error) => tracing::warn!(%error, "failed to count pending actions"),
+        }
+
+        let failures = Mutex::new(Vec::new());
 
         stream::iter(actions)
-            .for_each_concurrent(10, async |queue_item| {
+            .for_each_concurrent(config.max_concurrent_subscriptions, async |queue_item| {
+                // There is nothing to refresh, so the action is recorded as skipped rather than
+                // as a success or a failure that would be retried
+                if queue_item.action() == SubscriptionAction::Refresh
+                    && !queue_item.has_active_subscription()
+                {
+                    if let Err(error) = queue_item
+                        .skip("refresh action queued without an active subscription")
+                        .await
+                    {
+                        tracing::error!(%error, "failed to save skipped result");
+                    }
+
+                    return;
+                }
+
+                let channel_id = queue_item.channel_id().to_owned();
                 let result = queue_item
-                    .process::<_, reqwest::Error>(async |queue_item, active_subscription| {
-                        let topic = topic(&queue_item.channel_id);
-
-                        let mode = match queue_item.action {
-                            SubscriptionAction::Subscribe => Mode::Subscribe,
-                            SubscriptionAction::Unsubscribe => Mode::Unsubscribe,
-                            SubscriptionAction::Refresh if active_subscription.is_some() => {
-                                Mode::Subscribe
-                            }
-                            SubscriptionAction::Refresh => {
-                                tracing::warn!(
-                                    ?queue_item,
-                                    "refresh action queued without an active subscription"
-                                );
-                                return Ok(());
-                            }
-                        };
-
-                        let request = client
-                            .post("https://pubsubhubbub.appspot.com/subscribe")
-                            .form(&HubRequest {
-                                mode,
-                                callback: &callback,
-                                verify: Verify::Synchronous,
-                                topic,
-                            })
-                            .build()?;
-
-                        client.execute(request).await?.error_for_status()?;
-
-                        Ok(())
-                    })
+                    .process::<_, ProcessError>(
+                        Some(SUBSCRIPTION_ACTION_TIMEOUT),
+                        async |queue_item, _active_subscription| {
+                            let topic = topic(&queue_item.channel_id);
+
+                            let mode = match queue_item.action {
+                                // Refreshes without an active subscription were skipped above
+                                SubscriptionAction::Subscribe
+                                | SubscriptionAction::ForceSubscribe
+                                | SubscriptionAction::Refresh => Mode::Subscribe,
+                                SubscriptionAction::Unsubscribe => Mode::Unsubscribe,
+                                SubscriptionAction::PauseNotifications
+                                | SubscriptionAction::ResumeNotifications => {
+                                    let paused =
+                                        queue_item.action == SubscriptionAction::PauseNotifications;
+
+                                    if !KnownChannels::set_notifications_paused(
+                                        &database,
+                                        &queue_item.channel_id,
+                                        paused,
+                                    )
+                                    .await?
+                                    {
+                                        tracing::warn!(
+                                            ?queue_item,
+                                            "notifications toggled for an unknown channel"
+                                        );
+                                    }
+
+                                    return Ok(());
+                                }
+                            };
+
+                            let request = client
+                                .post("https://pubsubhubbub.appspot.com/subscribe")
+                                .form(&HubRequest {
+                                    mode,
+                                    callback: &callback,
+                                    verify: config.pubsub_verify_mode,
+                                    secret: queue_item.secret.as_deref(),
+                                    topic,
+                                })
+                                .build()?;
+
+                            SubscriptionQueue::mark_sent(&database, queue_item.id).await?;
+
+                            client
+                                .execute(request)
+                                .await
+                                .and_then(|response| response.error_for_status())?;
+
+                            Ok(())
+                        },
+                    )
                     .await;
 
-                if let Err(error) = result {
-                    tracing::error!(%error, "failed to save processed results")
+                match result {
+                    Ok(Some(error)) => failures
+                        .lock()
+                        .expect("mutex should not be poisoned")
+                        .push((channel_id, error)),
+                    Ok(None) => {}
+                    Err(error) => tracing::error!(%error, "failed to save processed results"),
                 }
             })
             .await;
 
+        // Only alert once an action has used up its retries, rather than on every failed attempt
+        let failures = failures.into_inner().expect("mutex should not be poisoned");
+        if !failures.is_empty() {
+            alerts
+                .send_alert(AlertKind::SubscriptionFailed { failures })
+                .await;
+        }
+
+        // Wake up for the next scheduled retry, if there is one
+        let next_scheduled =
+            SubscriptionQueue::get_next_scheduled(&database, config.subscription_max_retries)
+                .await
+                .inspect_err(
+                    |error| tracing::error!(%error, "failed to get next scheduled action"),
+                )?;
+        let retry_delay = next_scheduled.map(|scheduled_for| {
+            Timestamp::now()
+                .duration_until(scheduled_for)
+                .max(SignedDuration::ZERO)
+                .try_into()
+                .expect("duration should never be negative")
+        });
+
         tokio::select! {
-            _ = notify.notified() => tracing::trace!("pubsub notification received"),
+            _ = idle(notify.notified()) => tracing::trace!("pubsub notification received"),
+            _ = idle(tokio::time::sleep(retry_delay.unwrap_or_default())), if retry_delay.is_some() => tracing::trace!("scheduled retry ready"),
             _ = shutdown.cancelled() => break,
         }
     }
@@ -1,39 +1,70 @@
 use std::{sync::Arc, time::Duration};
 
-use entity_types::subscription_queue::SubscriptionAction;
+use entity_types::subscription_queue::{SubscriptionAction, SubscriptionSource};
 use jiff::{SignedDuration, Timestamp};
 use sea_orm::{DatabaseConnection, DbErr};
 use tokio::sync::Notify;
 use tokio_util::sync::CancellationToken;
 
-use crate::database::{ActiveSubscriptions, SubscriptionQueue};
+use crate::{
+    alert::{AlertKind, AlertSender},
+    config::Config,
+    database::{ActiveSubscriptions, SubscriptionQueue, SubscriptionRefreshState},
+    task_metrics::idle,
+};
 
+#[tracing::instrument(skip_all)]
 pub async fn pubsub_refresh(
     shutdown: CancellationToken,
     database: DatabaseConnection,
     notify: Arc<Notify>,
+    config: Arc<Config>,
+    alerts: AlertSender,
 ) -> Result<(), DbErr> {
     let refresh_window = SignedDuration::from_secs(60 * 60 * 24);
     let refresh_delay = SignedDuration::from_secs(60 * 60);
 
-    loop {
-        let soonest_expiration = ActiveSubscriptions::get_soonest_expiration(&database)
-            .await
-            .inspect_err(|error| tracing::error!(%error, "failed to get soonest expiration"))?;
+    // Resume the previous sleep instead of recalculating it after a restart
+    let mut stored_wakeup = SubscriptionRefreshState::get_next_wakeup(&database)
+        .await
+        .inspect_err(|error| tracing::error!(%error, "failed to get next refresh wakeup"))?;
 
-        let delay = match soonest_expiration {
-            Some(expiration) => Timestamp::now()
-                .duration_until(expiration)
-                .saturating_sub(refresh_window.saturating_sub(refresh_delay))
-                .try_into()
-                .expect("duration should never be negative"),
+    // Unconfirmed refreshes leave the soonest expiration in the past, so never wake up
+    // sooner than `refresh_delay` after the previous pass
+    let mut earliest_wakeup: Option<Timestamp> = None;
 
-            None => Duration::from_secs(24 * 60 * 60), // No subscriptions, wait a day
+    loop {
+        let next_wakeup_at = match stored_wakeup.take() {
+            Some(next_wakeup_at) => next_wakeup_at,
+            None => {
+                let soonest_expiration = ActiveSubscriptions::get_soonest_expiration(&database)
+                    .await
+                    .inspect_err(
+                        |error| tracing::error!(%error, "failed to get soonest expiration"),
+                    )?;
+
+                match soonest_expiration {
+                    Some(expiration) => expiration - refresh_window.saturating_sub(refresh_delay),
+                    None => Timestamp::now() + SignedDuration::from_hours(24), // No subscriptions, wait a day
+                }
+            }
         };
+        let next_wakeup_at =
+            earliest_wakeup.map_or(next_wakeup_at, |earliest| next_wakeup_at.max(earliest));
+
+        SubscriptionRefreshState::set_next_wakeup(&database, next_wakeup_at)
+            .await
+            .inspect_err(|error| tracing::error!(%error, "failed to save next refresh wakeup"))?;
+
+        let delay: Duration = Timestamp::now()
+            .duration_until(next_wakeup_at)
+            .max(SignedDuration::ZERO)
+            .try_into()
+            .expect("duration should never be negative");
 
         tokio::select! {
             _ = shutdown.cancelled() => break,
-            _ = tokio::time::sleep(delay) => {},
+            _ = idle(tokio::time::sleep(delay)) => {},
         }
 
         let expiring =
@@ -43,15 +74,48 @@ pub async fn pubsub_refresh(
                     |error| tracing::error!(%error, "failed to get expiring subscriptions"),
                 )?;
 
-        SubscriptionQueue::add_actions(
+        // Channels with an action still waiting to be processed do not need another refresh
+        let mut refreshes = Vec::with_capacity(expiring.len());
+        for model in expiring {
+            let pending =
+                SubscriptionQueue::count_pending_for_channel(&database, &model.channel_id)
+                    .await
+                    .inspect_err(
+                        |error| tracing::error!(%error, "failed to count pending actions"),
+                    )?;
+
+            if pending == 0 {
+                refreshes.push((model.channel_id, SubscriptionAction::Refresh));
+            }
+        }
+
+        earliest_wakeup = Some(Timestamp::now() + refresh_delay);
+
+        match SubscriptionQueue::add_actions(
             &database,
             &notify,
-            expiring
-                .into_iter()
-                .map(|model| (model.channel_id, SubscriptionAction::Refresh)),
+            config.subscription_queue_max_rows,
+            config.subscription_max_retries,
+            refreshes,
+            SubscriptionSource::ExpirationRefresh,
         )
         .await
-        .inspect_err(|error| tracing::error!(%error, "failed to insert subscription refreshes"))?
+        {
+            Ok(()) => {}
+            Err(error) if SubscriptionQueue::is_queue_full(&error) => {
+                tracing::error!(%error, "subscription queue is full, skipping refreshes");
+
+                alerts
+                    .send_alert(AlertKind::SubscriptionQueueFull {
+                        max_rows: config.subscription_queue_max_rows,
+                    })
+                    .await;
+            }
+            Err(error) => {
+                tracing::error!(%error, "failed to insert subscription refreshes");
+                return Err(error);
+            }
+        }
     }
 
     tracing::info!("shutting down");
@@ -7,25 +7,42 @@ use std::{
 
 use axum::http::{HeaderMap, HeaderValue};
 use entity::known_channels;
-use entity_types::subscription_queue::SubscriptionAction;
-use google_youtube3::api::SubscriptionListResponse;
+use entity_types::{
+    JiffTimestampMilliseconds,
+    subscription_queue::{SubscriptionAction, SubscriptionSource},
+};
+use google_youtube3::api::{Channel, ChannelListResponse, SubscriptionListResponse};
+use jiff::Timestamp;
 use oauth2::AccessToken;
 use reqwest::{StatusCode, header};
 use sea_orm::{DatabaseConnection, DbErr};
-use tokio::sync::Notify;
+use tokio::{sync::Notify, time::Instant};
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    database::{ActiveSubscriptions, KnownChannels, SubscriptionQueue},
+    alert::{AlertKind, AlertSender},
+    config::Config,
+    database::{
+        ActiveSubscriptions, DEFAULT_PRIORITY, KnownChannels, SubscriptionQueue,
+        SubscriptionSyncRuns,
+    },
     oauth::TokenManager,
+    quota::{ApiCall, send_recorded},
+    task_metrics::idle,
 };
 
+const YOUTUBE_API_TIMEOUT_SECS: u64 = 30;
+const YOUTUBE_API_TIMEOUT: Duration = Duration::from_secs(YOUTUBE_API_TIMEOUT_SECS);
+
+#[tracing::instrument(skip_all)]
 pub async fn subscription_manager(
     shutdown: CancellationToken,
     database: DatabaseConnection,
     notify: Arc<Notify>,
     client: reqwest::Client,
     token_manager: TokenManager,
+    config: Arc<Config>,
+    alerts: AlertSender,
 ) -> Result<(), DbErr> {
     // One hour
     let mut update_interval = tokio::time::interval(Duration::from_secs(60 * 60));
@@ -36,7 +53,7 @@ pub async fn subscription_manager(
     loop {
         tokio::select! {
             _ = shutdown.cancelled() => break,
-            _ = update_interval.tick() => {},
+            _ = idle(update_interval.tick()) => {},
         }
 
         let previous_channel_ids = ActiveSubscriptions::get_all_channel_ids(&database)
@@ -48,11 +65,38 @@ pub async fn subscription_manager(
             token_result = token_manager.wait_for_token() => token_result.inspect_err(|error| tracing::error!(%error, "failed to get current token"))?,
         };
 
-        let current_channels = match get_all_subscriptions(&client, &mut last_etag, token).await {
-            Some(channel_ids) => channel_ids,
-            None => break, // TODO: this is both on error and on no update
+        let sync_start = Instant::now();
+
+        // Failures are logged, try again on the next update
+        let Some(subscriptions) =
+            get_all_subscriptions(&database, &client, &mut last_etag, token.clone()).await
+        else {
+            continue;
+        };
+
+        let Some(current_channels) = subscriptions.channels else {
+            SubscriptionSyncRuns::record(
+                &database,
+                0,
+                0,
+                subscriptions.api_pages,
+                sync_start.elapsed(),
+                true,
+            )
+            .await
+            .inspect_err(
+                |error| tracing::error!(%error, "failed to record subscription sync run"),
+            )?;
+
+            continue;
         };
 
+        // The `@handle` and subscriber count of each channel are not included in the subscriptions api response
+        let channels = get_channels(&database, &client, &token, current_channels.keys())
+            .await
+            .inspect_err(|error| tracing::warn!(%error, "failed to fetch channel details"))
+            .unwrap_or_default();
+
         let updated_channels =
             current_channels
                 .iter()
@@ -60,6 +104,22 @@ pub async fn subscription_manager(
                     channel_id: channel_id.clone(),
                     channel_name: metadata.name.clone(),
                     channel_profile_picture: metadata.profile_picture.clone(),
+                    is_shorts_channel: false,
+                    video_age_threshold_minutes: None,
+                    last_video_at: None,
+                    handle: channels
+                        .get(channel_id)
+                        .and_then(|channel| channel.snippet.as_ref()?.custom_url.clone()),
+                    shorts_ratio: None,
+                    shorts_ratio_samples: 0,
+                    notifications_paused: false,
+                    subscription_count: metadata
+                        .total_item_count
+                        .and_then(|count| count.try_into().ok()),
+                    subscriber_count: channels.get(channel_id).and_then(subscriber_count),
+                    priority: DEFAULT_PRIORITY,
+                    metadata_updated_at: Some(JiffTimestampMilliseconds(Timestamp::now())),
+                    profile_picture_updated_at: Some(JiffTimestampMilliseconds(Timestamp::now())),
                 });
 
         KnownChannels::add_channels(&database, updated_channels)
@@ -70,19 +130,69 @@ pub async fn subscription_manager(
 
         let current_channel_ids = HashSet::from_iter(current_channels.keys().cloned());
 
-        let added_channels = current_channel_ids.difference(&previous_channel_ids);
-        let removed_channels = previous_channel_ids.difference(&current_channel_ids);
+        let added_channels = Vec::from_iter(current_channel_ids.difference(&previous_channel_ids));
+        let removed_channels =
+            Vec::from_iter(previous_channel_ids.difference(&current_channel_ids));
 
-        let added_actions =
-            added_channels.map(|channel_id| (channel_id.clone(), SubscriptionAction::Subscribe));
+        let added_actions = added_channels
+            .iter()
+            .map(|&channel_id| (channel_id.clone(), SubscriptionAction::Subscribe));
         let removed_actions = removed_channels
-            .map(|channel_id| (channel_id.clone(), SubscriptionAction::Unsubscribe));
+            .iter()
+            .map(|&channel_id| (channel_id.clone(), SubscriptionAction::Unsubscribe));
+
+        match SubscriptionQueue::add_actions(
+            &database,
+            &notify,
+            config.subscription_queue_max_rows,
+            config.subscription_max_retries,
+            added_actions.chain(removed_actions),
+            SubscriptionSource::Hourly,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err(error) if SubscriptionQueue::is_queue_full(&error) => {
+                tracing::error!(%error, "subscription queue is full, skipping this sync");
+
+                alerts
+                    .send_alert(AlertKind::SubscriptionQueueFull {
+                        max_rows: config.subscription_queue_max_rows,
+                    })
+                    .await;
+
+                // Force the next sync to recompute the diff instead of hitting the etag cache
+                last_etag = None;
+                continue;
+            }
+            Err(error) => {
+                tracing::error!(%error, "failed to add actions to subscription queue");
+                return Err(error);
+            }
+        }
 
-        SubscriptionQueue::add_actions(&database, &notify, added_actions.chain(removed_actions))
+        SubscriptionSyncRuns::record(
+            &database,
+            added_channels.len().try_into().unwrap_or(i32::MAX),
+            removed_channels.len().try_into().unwrap_or(i32::MAX),
+            subscriptions.api_pages,
+            sync_start.elapsed(),
+            false,
+        )
+        .await
+        .inspect_err(|error| tracing::error!(%error, "failed to record subscription sync run"))?;
+
+        let stats = ActiveSubscriptions::get_subscription_stats(&database)
             .await
-            .inspect_err(
-                |error| tracing::error!(%error, "failed to add actions to subscription queue"),
-            )?;
+            .inspect_err(|error| tracing::error!(%error, "failed to get subscription stats"))?;
+
+        tracing::info!(
+            total = stats.total,
+            expiring_in_1_day = stats.expiring_in_1_day,
+            expiring_in_7_days = stats.expiring_in_7_days,
+            already_expired = stats.already_expired,
+            "subscriptions synced"
+        );
     }
 
     tracing::info!("shutting down");
@@ -90,17 +200,27 @@ pub async fn subscription_manager(
     Ok(())
 }
 
+struct Subscriptions {
+    /// [`None`] if the subscriptions have not changed since the last etag
+    channels: Option<HashMap<String, ChannelMetadata>>,
+    api_pages: i32,
+}
+
 struct ChannelMetadata {
     name: String,
     profile_picture: String,
+    /// Only included for some subscription types
+    total_item_count: Option<u32>,
 }
 
 async fn get_all_subscriptions(
+    database: &DatabaseConnection,
     client: &reqwest::Client,
     last_etag: &mut Option<String>,
     token: AccessToken,
-) -> Option<HashMap<String, ChannelMetadata>> {
+) -> Option<Subscriptions> {
     let mut page_token = None;
+    let mut api_pages = 0;
     let url = "https://www.googleapis.com/youtube/v3/subscriptions?part=snippet,contentDetails&mine=true&maxResults=50";
 
     let mut channel_ids = HashMap::new();
@@ -119,20 +239,42 @@ async fn get_all_subscriptions(
             HeaderMap::new()
         };
 
-        let response = client
-            .get(url.as_ref())
-            .bearer_auth(token.secret())
-            .headers(headers)
-            .send()
-            .await
-            .unwrap();
+        let request = send_recorded(
+            database,
+            client
+                .get(url.as_ref())
+                .bearer_auth(token.secret())
+                .headers(headers),
+            ApiCall {
+                endpoint: "subscriptions.list",
+                quota_cost: 1,
+                video_id: None,
+                channel_id: None,
+            },
+        );
+
+        let response = match tokio::time::timeout(YOUTUBE_API_TIMEOUT, request).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(error)) => {
+                tracing::warn!(%error, "failed to request subscriptions page");
+                break None;
+            }
+            Err(_) => {
+                tracing::warn!(timeout = ?YOUTUBE_API_TIMEOUT, "timed out requesting subscriptions page");
+                break None;
+            }
+        };
 
+        api_pages += 1;
         let status = response.status();
 
         if status == StatusCode::NOT_MODIFIED {
             // TODO: in database?
             tracing::info!("not changed");
-            break None;
+            break Some(Subscriptions {
+                channels: None,
+                api_pages,
+            });
         }
 
         if !status.is_success() {
@@ -176,6 +318,9 @@ async fn get_all_subscriptions(
                 ChannelMetadata {
                     name: channel_name,
                     profile_picture: channel_thumbnail.url.unwrap(),
+                    total_item_count: subscription
+                        .content_details
+                        .and_then(|content_details| content_details.total_item_count),
                 },
             );
         }
@@ -183,7 +328,90 @@ async fn get_all_subscriptions(
         page_token = json.next_page_token;
 
         if page_token.is_none() {
-            break Some(channel_ids);
+            break Some(Subscriptions {
+                channels: Some(channel_ids),
+                api_pages,
+            });
         }
     }
 }
+
+/// Url of the smallest available thumbnail of the channel
+pub fn profile_picture(channel: &Channel) -> Option<String> {
+    let thumbnails = channel.snippet.as_ref()?.thumbnails.as_ref()?;
+    let thumbnail = thumbnails
+        .default
+        .as_ref()
+        .or(thumbnails.standard.as_ref())
+        .or(thumbnails.medium.as_ref())
+        .or(thumbnails.high.as_ref())
+        .or(thumbnails.maxres.as_ref())?;
+
+    thumbnail.url.clone()
+}
+
+/// Subscriber count of the channel, unless it is hidden
+pub fn subscriber_count(channel: &Channel) -> Option<i64> {
+    let statistics = channel.statistics.as_ref()?;
+
+    if statistics.hidden_subscriber_count == Some(true) {
+        return None;
+    }
+
+    statistics.subscriber_count?.try_into().ok()
+}
+
+/// Look up the snippet and statistics of each channel, channels that do not exist are left out
+pub async fn get_channels(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    token: &AccessToken,
+    channel_ids: impl IntoIterator<Item = &String>,
+) -> Result<HashMap<String, Channel>, reqwest::Error> {
+    let channel_ids = Vec::from_iter(channel_ids);
+    let mut channels = HashMap::new();
+
+    // The channels api accepts at most 50 ids per request
+    for chunk in channel_ids.chunks(50) {
+        let ids = chunk
+            .iter()
+            .map(|id| id.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let request = client
+            .get("https://www.googleapis.com/youtube/v3/channels")
+            .query(&[
+                ("part", "snippet,statistics"),
+                ("id", &ids),
+                ("maxResults", "50"),
+            ])
+            .bearer_auth(token.secret())
+            .timeout(YOUTUBE_API_TIMEOUT);
+
+        let response = send_recorded(
+            database,
+            request,
+            ApiCall {
+                endpoint: "channels.list",
+                quota_cost: 1,
+                video_id: None,
+                channel_id: None,
+            },
+        )
+        .await?
+        .error_for_status()?
+        .json::<ChannelListResponse>()
+        .await?;
+
+        channels.extend(
+            response
+                .items
+                .into_iter()
+                .flatten()
+                .filter_map(|channel| Some((channel.id.clone()?, channel))),
+        );
+    }
+
+    Ok(channels)
+}
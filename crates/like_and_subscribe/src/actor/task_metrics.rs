@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::task_metrics::{TaskMonitors, idle};
+
+/// Mean poll durations above this indicate a blocking operation inside of a task
+const MEAN_POLL_DURATION_WARN_THRESHOLD: Duration = Duration::from_millis(50);
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+pub async fn task_metrics_watchdog(shutdown: CancellationToken, monitors: TaskMonitors) {
+    let mut previous = monitors.metrics();
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = idle(tokio::time::sleep(CHECK_INTERVAL)) => {},
+        }
+
+        let current = monitors.metrics();
+
+        for (metrics, earlier) in current.iter().zip(&previous) {
+            let interval = metrics.since(earlier);
+            let mean_poll_duration = interval.mean_poll_duration();
+
+            if mean_poll_duration > MEAN_POLL_DURATION_WARN_THRESHOLD {
+                tracing::warn!(
+                    task = interval.name,
+                    ?mean_poll_duration,
+                    poll_count = interval.poll_count,
+                    slow_poll_percentage = interval.slow_poll_percentage(),
+                    "task mean poll duration is too long, something may be blocking"
+                );
+            }
+        }
+
+        previous = current;
+    }
+
+    tracing::info!("shutting down");
+}
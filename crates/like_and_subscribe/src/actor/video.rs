@@ -0,0 +1,360 @@
+use std::sync::Arc;
+
+use entity::{known_channels, video_queue};
+use entity_types::video_queue::{DetectionMethod, ShortsDetectionMethod, VideoAction};
+use futures::{StreamExt as _, stream};
+use oauth2::AccessToken;
+use sea_orm::{DatabaseConnection, DbErr};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    alert::{AlertKind, AlertSender},
+    config::Config,
+    database::{KnownChannels, ProcessedVideo, ShortsDetectionCache, VideoQueue},
+    oauth::TokenManager,
+    playlist::{self, ApiError, shorts::check_redirect},
+    task_metrics::idle,
+    util::race_fallback,
+};
+
+/// Videos from channels with a higher shorts ratio are assumed to be shorts if they cannot be checked
+const SHORTS_RATIO_THRESHOLD: f64 = 0.8;
+/// Verified detections needed before the shorts ratio of a channel is trusted at all
+const SHORTS_RATIO_MIN_SAMPLES: i32 = 10;
+
+/// Privacy status of videos that can be added to the playlist
+const PUBLIC_VISIBILITY: &str = "public";
+
+pub async fn video_queue_consumer(
+    shutdown: CancellationToken,
+    database: DatabaseConnection,
+    notify: Arc<Notify>,
+    client: reqwest::Client,
+    token_manager: TokenManager,
+    config: Arc<Config>,
+    alerts: AlertSender,
+) -> Result<(), DbErr> {
+    loop {
+        // Release the database connection straight away if shutting down
+        let videos = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            videos = VideoQueue::get_pending_videos(&database) => videos.inspect_err(
+                |error| tracing::error!(%error, "failed to get pending videos from database"),
+            )?,
+        };
+
+        if !videos.is_empty() {
+            let token = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                token_result = token_manager.wait_for_token() => token_result.inspect_err(|error| tracing::error!(%error, "failed to get current token"))?,
+            };
+
+            stream::iter(videos)
+                .for_each_concurrent(config.max_concurrent_videos, async |queue_item| {
+                    let result = queue_item
+                        .process(async |video, channel| {
+                            process_video(
+                                &database, &client, &token, &config, &alerts, video, channel,
+                            )
+                            .await
+                        })
+                        .await;
+
+                    if let Err(error) = result {
+                        tracing::error!(%error, "failed to save processed results")
+                    }
+                })
+                .await;
+        }
+
+        tokio::select! {
+            _ = idle(notify.notified()) => tracing::trace!("video notification received"),
+            _ = shutdown.cancelled() => break,
+        }
+    }
+
+    tracing::info!("shutting down");
+
+    Ok(())
+}
+
+async fn process_video(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    token: &AccessToken,
+    config: &Config,
+    alerts: &AlertSender,
+    video: &video_queue::Model,
+    channel: Option<&known_channels::Model>,
+) -> ProcessedVideo {
+    let Some(channel) = channel else {
+        tracing::warn!(?video, "feed item had unknown channel");
+        return ProcessedVideo {
+            action: VideoAction::SkippedUnknownChannel,
+            shorts_redirect: false,
+            shorts_detection_method: None,
+            playlist_id: None,
+            playlist_item_id: None,
+            error_message: None,
+            visibility: None,
+        };
+    };
+
+    let video_age_minutes = video
+        .published_at
+        .0
+        .duration_until(video.updated_at.0)
+        .as_secs_f64()
+        / 60.0;
+
+    let video_age_threshold_minutes = channel
+        .video_age_threshold_minutes
+        .unwrap_or(config.video_age_minutes_threshold);
+
+    if video_age_minutes > video_age_threshold_minutes {
+        tracing::debug!(
+            video_id = video.video_id,
+            video_age_minutes,
+            "ignoring updated old video"
+        );
+        return ProcessedVideo {
+            action: VideoAction::SkippedOld,
+            shorts_redirect: false,
+            shorts_detection_method: None,
+            playlist_id: None,
+            playlist_item_id: None,
+            error_message: None,
+            visibility: None,
+        };
+    }
+
+    let playlist_id = config.playlist_id_for(&channel.channel_id);
+
+    // Check if the video is a short
+    let detect_shorts = detect_shorts(
+        database,
+        client,
+        channel,
+        &video.video_id,
+        video.is_short_hint,
+    );
+
+    // Duplicate detection
+    let detect_duplicate = async {
+        match playlist::contains_video(database, client, token, playlist_id, &video.video_id).await
+        {
+            Ok(true) => {
+                tracing::warn!(
+                    video_id = video.video_id,
+                    "video exists in playlist already, skipping"
+                );
+                true
+            }
+            Ok(false) => false,
+            Err(error) => {
+                if let ApiError::QuotaExceeded = error {
+                    alerts.send_alert(AlertKind::QuotaExceeded).await;
+                }
+
+                tracing::warn!(video_id = video.video_id, %error, "failed to check if video exists in playlist already");
+                false
+            }
+        }
+    };
+
+    // Left as `None` if a duplicate is found before shorts detection finishes
+    let mut detection = None;
+
+    // Concurrent short circuiting || (or)
+    let skip = race_fallback(
+        async {
+            let result = detect_shorts.await;
+            detection = Some(result);
+            result.is_short.then_some(VideoAction::SkippedShort)
+        },
+        async {
+            detect_duplicate
+                .await
+                .then_some(VideoAction::SkippedDuplicate)
+        },
+        Option::is_some,
+    )
+    .await;
+
+    let shorts_redirect = detection.is_some_and(|detection| detection.is_short);
+    let shorts_detection_method = detection.map(|detection| detection.method);
+
+    if let Some(action) = skip {
+        return ProcessedVideo {
+            action,
+            shorts_redirect,
+            shorts_detection_method,
+            playlist_id: Some(playlist_id.to_owned()),
+            playlist_item_id: None,
+            error_message: None,
+            visibility: None,
+        };
+    }
+
+    // Only checked once the video would otherwise be inserted, to save quota
+    let visibility =
+        match playlist::video_visibility(database, client, token, &video.video_id).await {
+            Ok(visibility) => visibility,
+            Err(error) => {
+                if let ApiError::QuotaExceeded = error {
+                    alerts.send_alert(AlertKind::QuotaExceeded).await;
+                }
+
+                tracing::warn!(video_id = video.video_id, %error, "failed to get video visibility");
+                None
+            }
+        };
+
+    if let Some(visibility) = &visibility
+        && visibility != PUBLIC_VISIBILITY
+    {
+        tracing::trace!(
+            video_id = video.video_id,
+            visibility,
+            "ignoring video that is not public"
+        );
+        return ProcessedVideo {
+            action: VideoAction::SkippedNotPublic,
+            shorts_redirect,
+            shorts_detection_method,
+            playlist_id: Some(playlist_id.to_owned()),
+            playlist_item_id: None,
+            error_message: None,
+            visibility: Some(visibility.clone()),
+        };
+    }
+
+    tracing::trace!(video_id = video.video_id, "inserting new video");
+    let (action, playlist_item_id, error_message) =
+        match playlist::insert_video(database, client, token, playlist_id, &video.video_id).await {
+            Ok(item) => {
+                tracing::debug!(video_id = video.video_id, "video inserted");
+                (VideoAction::Inserted, item.id, None)
+            }
+            Err(error) => {
+                if let ApiError::QuotaExceeded = error {
+                    alerts.send_alert(AlertKind::QuotaExceeded).await;
+                }
+
+                tracing::error!(video_id = video.video_id, %error, "failed to insert video");
+                (VideoAction::Failed, None, Some(error.to_string()))
+            }
+        };
+
+    ProcessedVideo {
+        action,
+        shorts_redirect,
+        shorts_detection_method,
+        playlist_id: Some(playlist_id.to_owned()),
+        playlist_item_id,
+        error_message,
+        visibility,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ShortsDetectionResult {
+    is_short: bool,
+    /// How `is_short` was decided
+    method: ShortsDetectionMethod,
+}
+
+async fn detect_shorts(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    channel: &known_channels::Model,
+    video_id: &str,
+    is_short_hint: Option<bool>,
+) -> ShortsDetectionResult {
+    if channel.is_shorts_channel {
+        tracing::debug!(video_id, "channel only posts shorts");
+        return ShortsDetectionResult {
+            is_short: true,
+            method: ShortsDetectionMethod::ChannelFlag,
+        };
+    }
+
+    match ShortsDetectionCache::get(database, video_id).await {
+        Ok(Some(is_short)) => {
+            tracing::debug!(video_id, is_short, "using cached short status");
+            return ShortsDetectionResult {
+                is_short,
+                method: ShortsDetectionMethod::Cached,
+            };
+        }
+        Ok(None) => {}
+        Err(error) => {
+            tracing::warn!(video_id, %error, "failed to get cached short status");
+        }
+    }
+
+    // TODO: do something with the reason?
+    // Do not flag as a short if we are not sure
+    match check_redirect(database, video_id, client).await {
+        Ok(is_short) => {
+            if is_short {
+                tracing::debug!(video_id, "video is a short");
+            }
+
+            ShortsDetectionCache::record(database, video_id, is_short, DetectionMethod::Redirect)
+                .await
+                .unwrap_or_else(
+                    |error| tracing::warn!(video_id, %error, "failed to cache short status"),
+                );
+
+            KnownChannels::update_shorts_ratio(database, &channel.channel_id, is_short)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::warn!(channel_id = channel.channel_id, %error, "failed to update shorts ratio")
+                });
+
+            ShortsDetectionResult {
+                is_short,
+                method: ShortsDetectionMethod::Redirect,
+            }
+        }
+        Err(error) => {
+            // The feed hint and the ratio only break the tie when the video could not be checked,
+            // so neither is cached nor folded back into the ratio
+            if is_short_hint == Some(true) {
+                tracing::warn!(
+                    video_id,
+                    ?error,
+                    "unable to check if video is a short, feed hints that it is"
+                );
+                return ShortsDetectionResult {
+                    is_short: true,
+                    method: ShortsDetectionMethod::FeedHint,
+                };
+            }
+
+            if let Some(shorts_ratio) = channel.shorts_ratio
+                && channel.shorts_ratio_samples >= SHORTS_RATIO_MIN_SAMPLES
+                && shorts_ratio > SHORTS_RATIO_THRESHOLD
+            {
+                tracing::warn!(
+                    video_id,
+                    ?error,
+                    shorts_ratio,
+                    "unable to check if video is a short, channel mostly posts shorts"
+                );
+                return ShortsDetectionResult {
+                    is_short: true,
+                    method: ShortsDetectionMethod::Heuristic,
+                };
+            }
+
+            tracing::warn!(video_id, ?error, "unable to determine if video is a short");
+            ShortsDetectionResult {
+                is_short: false,
+                method: ShortsDetectionMethod::None,
+            }
+        }
+    }
+}
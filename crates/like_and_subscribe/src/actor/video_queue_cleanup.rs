@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use jiff::{SignedDuration, Timestamp};
+use sea_orm::{DatabaseConnection, DbErr};
+use tokio_util::sync::CancellationToken;
+
+use crate::{database::VideoQueue, task_metrics::idle};
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+
+/// Delete processed videos once they are older than the retention period, so the queue does not
+/// grow forever
+#[tracing::instrument(skip_all)]
+pub async fn video_queue_cleanup(
+    shutdown: CancellationToken,
+    database: DatabaseConnection,
+    retention: SignedDuration,
+) -> Result<(), DbErr> {
+    let mut cleanup_interval = tokio::time::interval(CLEANUP_INTERVAL);
+    cleanup_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = idle(cleanup_interval.tick()) => {},
+        }
+
+        let deleted = VideoQueue::delete_processed_before(&database, Timestamp::now() - retention)
+            .await
+            .inspect_err(|error| tracing::error!(%error, "failed to delete old videos"))?;
+
+        tracing::info!(deleted, "deleted old videos from the queue");
+    }
+
+    tracing::info!("shutting down");
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use axum_extra::response::InternalServerError;
+use entity::audit_log;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Deserialize;
+
+use crate::database::AuditLog;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogParams {
+    #[serde(default = "default_audit_log_limit")]
+    limit: u64,
+}
+
+fn default_audit_log_limit() -> u64 {
+    100
+}
+
+pub async fn audit_log(
+    Query(params): Query<AuditLogParams>,
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<Vec<audit_log::Model>>, InternalServerError<DbErr>> {
+    AuditLog::get_recent(&database, params.limit)
+        .await
+        .map(Json)
+        .map_err(InternalServerError)
+}
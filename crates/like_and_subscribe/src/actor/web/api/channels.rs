@@ -0,0 +1,528 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+    sync::Arc,
+};
+
+use axum::{
+    Json,
+    extract::{Multipart, Path, Query, State},
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use entity::known_channels;
+use entity_types::{
+    JiffTimestampMilliseconds,
+    subscription_queue::{SubscriptionAction, SubscriptionSource},
+};
+use jiff::Timestamp;
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use super::TOKEN_WAIT_TIMEOUT;
+use crate::{
+    actor::{
+        subscription::{get_channels, profile_picture, subscriber_count},
+        web::TailscaleUser,
+    },
+    config::Config,
+    database::{
+        AuditLog, DEFAULT_PRIORITY, KnownChannels, SubscriptionQueue, VideoHistoryEntry, VideoQueue,
+    },
+    feed::is_valid_channel_id,
+    oauth::TokenManager,
+};
+
+#[cfg(test)]
+mod test {
+    use crate::actor::web::api::channels::parse_channels_csv;
+
+    #[test]
+    fn parse_csv_with_optional_name() {
+        let csv = "channel_name,channel_id\r\n\"Some, Channel\",UCaaaaaaaaaaaaaaaaaaaaaa\n,UCbbbbbbbbbbbbbbbbbbbbbb\n\n";
+
+        assert_eq!(
+            parse_channels_csv(csv),
+            Some(vec![
+                (
+                    "UCaaaaaaaaaaaaaaaaaaaaaa".to_owned(),
+                    Some("Some, Channel".to_owned())
+                ),
+                ("UCbbbbbbbbbbbbbbbbbbbbbb".to_owned(), None),
+            ])
+        );
+        assert_eq!(parse_channels_csv("id\nUCaaaaaaaaaaaaaaaaaaaaaa"), None);
+    }
+}
+
+const SEARCH_RESULT_LIMIT: u64 = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    q: String,
+}
+
+pub async fn search(
+    Query(params): Query<SearchParams>,
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<Vec<known_channels::Model>>, InternalServerError<DbErr>> {
+    KnownChannels::search(&database, &params.q, SEARCH_RESULT_LIMIT)
+        .await
+        .map(Json)
+        .map_err(InternalServerError)
+}
+
+pub async fn channel(
+    Path(channel_id): Path<String>,
+    State(database): State<DatabaseConnection>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    let channel = KnownChannels::get_channel(&database, &channel_id)
+        .await
+        .map_err(InternalServerError)?;
+
+    Ok(match channel {
+        Some(channel) => Json(channel).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    })
+}
+
+const MAX_PAGE_SIZE: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct VideosParams {
+    #[serde(default = "default_videos_limit")]
+    limit: u64,
+    #[serde(default)]
+    offset: u64,
+}
+
+fn default_videos_limit() -> u64 {
+    50
+}
+
+pub async fn videos(
+    Path(channel_id): Path<String>,
+    Query(params): Query<VideosParams>,
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<Vec<VideoHistoryEntry>>, InternalServerError<DbErr>> {
+    VideoQueue::get_video_history_for_channel(
+        &database,
+        &channel_id,
+        params.limit.min(MAX_PAGE_SIZE),
+        params.offset,
+    )
+    .await
+    .map(Json)
+    .map_err(InternalServerError)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelSettings {
+    is_shorts_channel: Option<bool>,
+    /// From 1 (highest) to 10 (lowest), see [`CHANNEL_PRIORITIES`]
+    priority: Option<i32>,
+}
+
+/// Videos and subscription actions of channels with a lower value are processed first
+const CHANNEL_PRIORITIES: RangeInclusive<i32> = 1..=10;
+
+pub async fn update(
+    TailscaleUser(actor): TailscaleUser,
+    Path(channel_id): Path<String>,
+    State(database): State<DatabaseConnection>,
+    Json(settings): Json<ChannelSettings>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    if settings
+        .priority
+        .is_some_and(|priority| !CHANNEL_PRIORITIES.contains(&priority))
+    {
+        return Ok(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let updated = KnownChannels::update_settings(
+        &database,
+        &channel_id,
+        settings.is_shorts_channel,
+        settings.priority,
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    if !updated {
+        return Ok(StatusCode::NOT_FOUND);
+    }
+
+    if let Some(is_shorts_channel) = settings.is_shorts_channel {
+        AuditLog::record_audit_event(
+            &database,
+            "channels.update",
+            &actor,
+            Some(&format!("channel:{channel_id}")),
+            Some(&format!("is_shorts_channel={is_shorts_channel}")),
+        )
+        .await
+        .map_err(InternalServerError)?;
+    }
+
+    if let Some(priority) = settings.priority {
+        AuditLog::record_audit_event(
+            &database,
+            "channels.update",
+            &actor,
+            Some(&format!("channel:{channel_id}")),
+            Some(&format!("priority={priority}")),
+        )
+        .await
+        .map_err(InternalServerError)?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PauseNotifications {
+    paused: bool,
+}
+
+/// Queue pausing or resuming new upload notifications for the channel
+pub async fn pause(
+    TailscaleUser(actor): TailscaleUser,
+    Path(channel_id): Path<String>,
+    State((database, notify, config)): State<(DatabaseConnection, Arc<Notify>, Arc<Config>)>,
+    Json(PauseNotifications { paused }): Json<PauseNotifications>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    let (action, event) = if paused {
+        (SubscriptionAction::PauseNotifications, "channels.pause")
+    } else {
+        (SubscriptionAction::ResumeNotifications, "channels.resume")
+    };
+
+    queue_channel_action(
+        &database, &notify, &config, &actor, channel_id, action, event,
+    )
+    .await
+}
+
+/// Queue a subscription to the channel with a new secret, replacing any existing subscription
+pub async fn resubscribe(
+    TailscaleUser(actor): TailscaleUser,
+    Path(channel_id): Path<String>,
+    State((database, notify, config)): State<(DatabaseConnection, Arc<Notify>, Arc<Config>)>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    queue_channel_action(
+        &database,
+        &notify,
+        &config,
+        &actor,
+        channel_id,
+        SubscriptionAction::ForceSubscribe,
+        "channels.resubscribe",
+    )
+    .await
+}
+
+/// Queue unsubscribing from the channel, keeping it in the known channels
+///
+/// The active subscription is removed once the hub confirms the unsubscription
+pub async fn unsubscribe(
+    TailscaleUser(actor): TailscaleUser,
+    Path(channel_id): Path<String>,
+    State((database, notify, config)): State<(DatabaseConnection, Arc<Notify>, Arc<Config>)>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    queue_channel_action(
+        &database,
+        &notify,
+        &config,
+        &actor,
+        channel_id,
+        SubscriptionAction::Unsubscribe,
+        "channels.unsubscribe",
+    )
+    .await
+}
+
+async fn queue_channel_action(
+    database: &DatabaseConnection,
+    notify: &Notify,
+    config: &Config,
+    actor: &str,
+    channel_id: String,
+    action: SubscriptionAction,
+    event: &str,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    if KnownChannels::get_channel(database, &channel_id)
+        .await
+        .map_err(InternalServerError)?
+        .is_none()
+    {
+        return Ok(StatusCode::NOT_FOUND);
+    }
+
+    match SubscriptionQueue::add_single_action(
+        database,
+        notify,
+        config.subscription_queue_max_rows,
+        config.subscription_max_retries,
+        channel_id.clone(),
+        action,
+        SubscriptionSource::AdminManual,
+    )
+    .await
+    {
+        Ok(()) => {}
+        Err(error) if SubscriptionQueue::is_queue_full(&error) => {
+            return Ok(StatusCode::SERVICE_UNAVAILABLE);
+        }
+        Err(error) => return Err(InternalServerError(error)),
+    }
+
+    AuditLog::record_audit_event(
+        database,
+        event,
+        actor,
+        Some(&format!("channel:{channel_id}")),
+        None,
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+    imported: usize,
+    already_known: usize,
+    invalid: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct ImportState {
+    pub database: DatabaseConnection,
+    pub notify: Arc<Notify>,
+    pub client: reqwest::Client,
+    pub token_manager: TokenManager,
+    pub config: Arc<Config>,
+}
+
+/// Name of the multipart field containing the csv to import
+const IMPORT_FILE_FIELD: &str = "file";
+
+/// Import the channels from an uploaded csv file with a `channel_id` and optional
+/// `channel_name` column, subscribing to the ones that are not known yet
+pub async fn import(
+    TailscaleUser(actor): TailscaleUser,
+    State(ImportState {
+        database,
+        notify,
+        client,
+        token_manager,
+        config,
+    }): State<ImportState>,
+    mut multipart: Multipart,
+) -> Result<Response, InternalServerError<DbErr>> {
+    let body = loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.name() == Some(IMPORT_FILE_FIELD) => {
+                match field.text().await {
+                    Ok(body) => break body,
+                    Err(error) => return Ok(error.into_response()),
+                }
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return Ok((StatusCode::BAD_REQUEST, "missing file field").into_response());
+            }
+            Err(error) => return Ok(error.into_response()),
+        }
+    };
+
+    let Some(rows) = parse_channels_csv(&body) else {
+        return Ok((StatusCode::BAD_REQUEST, "missing channel_id column").into_response());
+    };
+
+    let known_channel_ids = HashSet::<String>::from_iter(
+        KnownChannels::get_all_channel_ids(&database)
+            .await
+            .map_err(InternalServerError)?,
+    );
+
+    let mut invalid = Vec::new();
+    let mut already_known = 0;
+    let mut new_channels = HashMap::new();
+
+    for (channel_id, channel_name) in rows {
+        if !is_valid_channel_id(&channel_id) {
+            invalid.push(channel_id);
+        } else if known_channel_ids.contains(&channel_id) {
+            already_known += 1;
+        } else {
+            new_channels.insert(channel_id, channel_name);
+        }
+    }
+
+    if !new_channels.is_empty() {
+        let token =
+            match tokio::time::timeout(TOKEN_WAIT_TIMEOUT, token_manager.wait_for_token()).await {
+                Ok(token) => token.map_err(InternalServerError)?,
+                Err(_) => {
+                    tracing::warn!("timed out waiting for a token to import channels");
+                    return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response());
+                }
+            };
+
+        let mut fetched = match get_channels(&database, &client, &token, new_channels.keys()).await
+        {
+            Ok(fetched) => fetched,
+            Err(error) => {
+                tracing::error!(%error, "failed to fetch imported channels");
+                return Ok(StatusCode::BAD_GATEWAY.into_response());
+            }
+        };
+
+        // Channels that youtube does not know about do not exist
+        new_channels.retain(|channel_id, _| {
+            let exists = fetched.contains_key(channel_id);
+            if !exists {
+                invalid.push(channel_id.clone());
+            }
+            exists
+        });
+
+        let channels = Vec::from_iter(new_channels.iter().filter_map(
+            |(channel_id, channel_name)| {
+                let channel = fetched.remove(channel_id)?;
+                let subscriber_count = subscriber_count(&channel);
+                let profile_picture = profile_picture(&channel)?;
+                let snippet = channel.snippet?;
+
+                Some(known_channels::Model {
+                    channel_id: channel_id.clone(),
+                    channel_name: channel_name.clone().or(snippet.title)?,
+                    channel_profile_picture: profile_picture,
+                    is_shorts_channel: false,
+                    video_age_threshold_minutes: None,
+                    last_video_at: None,
+                    handle: snippet.custom_url,
+                    shorts_ratio: None,
+                    shorts_ratio_samples: 0,
+                    notifications_paused: false,
+                    subscription_count: None,
+                    subscriber_count,
+                    priority: DEFAULT_PRIORITY,
+                    metadata_updated_at: Some(JiffTimestampMilliseconds(Timestamp::now())),
+                    profile_picture_updated_at: Some(JiffTimestampMilliseconds(Timestamp::now())),
+                })
+            },
+        ));
+
+        // Channels without the metadata needed to be known can not be imported either
+        new_channels.retain(|channel_id, _| {
+            let inserted = channels
+                .iter()
+                .any(|channel| &channel.channel_id == channel_id);
+            if !inserted {
+                invalid.push(channel_id.clone());
+            }
+            inserted
+        });
+
+        KnownChannels::add_channels(&database, channels)
+            .await
+            .map_err(InternalServerError)?;
+
+        match SubscriptionQueue::add_actions(
+            &database,
+            &notify,
+            config.subscription_queue_max_rows,
+            config.subscription_max_retries,
+            new_channels
+                .keys()
+                .map(|channel_id| (channel_id.clone(), SubscriptionAction::Subscribe)),
+            SubscriptionSource::NewChannelAdded,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err(error) if SubscriptionQueue::is_queue_full(&error) => {
+                return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response());
+            }
+            Err(error) => return Err(InternalServerError(error)),
+        }
+    }
+
+    let imported = new_channels.len();
+
+    AuditLog::record_audit_event(
+        &database,
+        "channels.import",
+        &actor,
+        None,
+        Some(&format!(
+            "imported {imported}, {already_known} already known, {} invalid",
+            invalid.len()
+        )),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(Json(ImportSummary {
+        imported,
+        already_known,
+        invalid,
+    })
+    .into_response())
+}
+
+/// Parse the `channel_id` and optional `channel_name` columns from each row of the csv,
+/// returning [`None`] if there is no `channel_id` column in the header
+fn parse_channels_csv(csv: &str) -> Option<Vec<(String, Option<String>)>> {
+    let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+
+    let header = parse_csv_line(lines.next()?);
+    let channel_id_column = header.iter().position(|column| column == "channel_id")?;
+    let channel_name_column = header.iter().position(|column| column == "channel_name");
+
+    Some(
+        lines
+            .map(|line| {
+                let mut fields = parse_csv_line(line);
+                let channel_name = channel_name_column
+                    .and_then(|column| fields.get_mut(column).map(std::mem::take))
+                    .filter(|channel_name| !channel_name.is_empty());
+                let channel_id = fields
+                    .get_mut(channel_id_column)
+                    .map(std::mem::take)
+                    .unwrap_or_default();
+
+                (channel_id, channel_name)
+            })
+            .collect(),
+    )
+}
+
+/// Split a csv line into its fields, handling quoted fields with commas and escaped quotes
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().expect("fields is never empty").push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            char => fields.last_mut().expect("fields is never empty").push(char),
+        }
+    }
+
+    fields
+        .into_iter()
+        .map(|field| field.trim().to_owned())
+        .collect()
+}
@@ -0,0 +1,9 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+
+use crate::config::Config;
+
+pub async fn config(State(config): State<Arc<Config>>) -> Json<Arc<Config>> {
+    Json(config)
+}
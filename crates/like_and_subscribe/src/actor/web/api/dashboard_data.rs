@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use axum_extra::response::InternalServerError;
+use entity::{subscription_queue, subscription_queue_result, video_queue, video_queue_result};
+use jiff::Timestamp;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Serialize;
+
+use crate::{
+    config::Config,
+    database::{
+        ActiveSubscriptions, KnownChannels, OAuth, SubscriptionQueue, SubscriptionStats, VideoQueue,
+    },
+};
+
+/// Number of the newest queue items of each kind that are included
+const RECENT_LIMIT: u64 = 20;
+
+#[derive(Debug, Serialize)]
+pub struct DashboardData {
+    /// [`None`] if the youtube api has not been authenticated
+    oauth_token: Option<OAuthToken>,
+    subscription_stats: SubscriptionStats,
+    recent_video_queue: Vec<VideoQueueItem>,
+    recent_subscription_queue: Vec<SubscriptionQueueItem>,
+    queue_depth: QueueDepth,
+    known_channels_count: u64,
+}
+
+/// The tokens themselves are left out
+#[derive(Debug, Serialize)]
+pub struct OAuthToken {
+    expires_at: Timestamp,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VideoQueueItem {
+    #[serde(flatten)]
+    video: video_queue::Model,
+    /// [`None`] if the video has not been processed yet
+    result: Option<video_queue_result::Model>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubscriptionQueueItem {
+    #[serde(flatten)]
+    action: subscription_queue::Model,
+    /// [`None`] if the action has not been processed yet
+    result: Option<subscription_queue_result::Model>,
+}
+
+/// Items waiting to be processed
+#[derive(Debug, Serialize)]
+pub struct QueueDepth {
+    videos: u64,
+    subscriptions: u64,
+}
+
+/// Everything shown on the dashboard, in a single request
+pub async fn dashboard_data(
+    State((database, config)): State<(DatabaseConnection, Arc<Config>)>,
+) -> Result<Json<DashboardData>, InternalServerError<DbErr>> {
+    let (
+        oauth_token,
+        subscription_stats,
+        recent_video_queue,
+        recent_subscription_queue,
+        pending_videos,
+        pending_subscriptions,
+        known_channels_count,
+    ) = tokio::try_join!(
+        OAuth::get_token(&database),
+        ActiveSubscriptions::get_subscription_stats(&database),
+        VideoQueue::get_page(&database, None, None, RECENT_LIMIT, 0),
+        SubscriptionQueue::get_recent(&database, RECENT_LIMIT),
+        VideoQueue::count_pending(&database),
+        SubscriptionQueue::count_all_pending(&database, config.subscription_max_retries),
+        KnownChannels::count(&database),
+    )
+    .map_err(InternalServerError)?;
+
+    Ok(Json(DashboardData {
+        oauth_token: oauth_token.map(|token| OAuthToken {
+            expires_at: token.expires_at,
+        }),
+        subscription_stats,
+        recent_video_queue: recent_video_queue
+            .into_iter()
+            .map(|(video, result)| VideoQueueItem { video, result })
+            .collect(),
+        recent_subscription_queue: recent_subscription_queue
+            .into_iter()
+            .map(|(action, result)| SubscriptionQueueItem { action, result })
+            .collect(),
+        queue_depth: QueueDepth {
+            videos: pending_videos,
+            subscriptions: pending_subscriptions,
+        },
+        known_channels_count,
+    }))
+}
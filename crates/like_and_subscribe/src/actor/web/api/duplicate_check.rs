@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+
+use super::TOKEN_WAIT_TIMEOUT;
+use crate::{config::Config, feed::is_valid_video_id, oauth::TokenManager, playlist};
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateCheckParams {
+    /// Defaults to the fallback playlist
+    playlist_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateCheck {
+    video_id: String,
+    playlist_id: String,
+    in_playlist: bool,
+}
+
+/// Run the duplicate detection from the video consumer against the playlist on youtube
+pub async fn duplicate_check(
+    Path(video_id): Path<String>,
+    Query(params): Query<DuplicateCheckParams>,
+    State((database, client, token_manager, config)): State<(
+        DatabaseConnection,
+        reqwest::Client,
+        TokenManager,
+        Arc<Config>,
+    )>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    if !is_valid_video_id(&video_id) {
+        return Ok(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+    }
+
+    let playlist_id = params
+        .playlist_id
+        .as_deref()
+        .unwrap_or_else(|| config.default_playlist_id());
+
+    if !config.playlist_ids().contains(playlist_id) {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    let token = match tokio::time::timeout(TOKEN_WAIT_TIMEOUT, token_manager.wait_for_token()).await
+    {
+        Ok(token) => token.map_err(InternalServerError)?,
+        Err(_) => {
+            tracing::warn!("timed out waiting for a token to check for a duplicate");
+            return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response());
+        }
+    };
+
+    let in_playlist =
+        match playlist::contains_video(&database, &client, &token, playlist_id, &video_id).await {
+            Ok(in_playlist) => in_playlist,
+            Err(error) => {
+                tracing::error!(%error, video_id, "failed to check if video exists in playlist");
+                return Ok(StatusCode::BAD_GATEWAY.into_response());
+            }
+        };
+
+    Ok(Json(DuplicateCheck {
+        video_id,
+        playlist_id: playlist_id.to_owned(),
+        in_playlist,
+    })
+    .into_response())
+}
@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use jiff::Timestamp;
+use quick_xml::escape::escape;
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use super::TOKEN_WAIT_TIMEOUT;
+use crate::{
+    actor::web::TailscaleUser,
+    config::Config,
+    database::{AuditLog, KnownChannels, VideoQueue},
+    feed::Feed,
+    oauth::TokenManager,
+    playlist::{self, LatestUpload},
+};
+
+#[cfg(test)]
+mod test {
+    use jiff::Timestamp;
+
+    use crate::{actor::web::api::feed_test::synthetic_feed, feed::Feed, playlist::LatestUpload};
+
+    #[test]
+    fn synthetic_feed_parses() {
+        let upload = LatestUpload {
+            video_id: "29w5v9DRHY0".to_owned(),
+            title: "Tom & Jerry <3".to_owned(),
+            published: "2025-07-31T09:14:50Z".parse().unwrap(),
+        };
+        let xml = synthetic_feed(
+            "UCHtv-7yDeac7OSfPJA_a6aA",
+            &upload,
+            Timestamp::from_second(1_754_000_000).unwrap(),
+        );
+
+        let feed = Feed::from_xml(&xml).unwrap();
+        feed.entry.validate().unwrap();
+
+        assert_eq!(feed.entry.video_id, upload.video_id);
+        assert_eq!(feed.entry.channel_id, "UCHtv-7yDeac7OSfPJA_a6aA");
+        assert_eq!(feed.entry.title, upload.title);
+        assert_eq!(feed.entry.published, upload.published);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedTest {
+    /// Synthetic notification, in the same shape as the ones sent by the hub
+    xml: String,
+    feed: Option<Feed>,
+    /// Why the feed could not be parsed or failed validation
+    error: Option<String>,
+    /// If the video was newly added to the video queue
+    queued: bool,
+}
+
+/// Build a notification for the channel's most recent upload and run it through the feed parser,
+/// queueing the video if it is valid
+#[derive(Clone)]
+pub struct FeedTestState {
+    pub database: DatabaseConnection,
+    pub video_queue_notify: Arc<Notify>,
+    pub client: reqwest::Client,
+    pub token_manager: TokenManager,
+    pub config: Arc<Config>,
+}
+
+pub async fn feed_test(
+    TailscaleUser(actor): TailscaleUser,
+    Path(channel_id): Path<String>,
+    State(FeedTestState {
+        database,
+        video_queue_notify,
+        client,
+        token_manager,
+        config,
+    }): State<FeedTestState>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    if KnownChannels::get_channel(&database, &channel_id)
+        .await
+        .map_err(InternalServerError)?
+        .is_none()
+    {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    let token = match tokio::time::timeout(TOKEN_WAIT_TIMEOUT, token_manager.wait_for_token()).await
+    {
+        Ok(token) => token.map_err(InternalServerError)?,
+        Err(_) => {
+            tracing::warn!("timed out waiting for a token to test the feed");
+            return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response());
+        }
+    };
+
+    let upload = match playlist::latest_upload(&database, &client, &token, &channel_id).await {
+        Ok(Some(upload)) => upload,
+        Ok(None) => return Ok(StatusCode::NOT_FOUND.into_response()),
+        Err(error) => {
+            tracing::error!(%error, channel_id, "failed to fetch the latest upload");
+            return Ok(StatusCode::BAD_GATEWAY.into_response());
+        }
+    };
+
+    let xml = synthetic_feed(&channel_id, &upload, Timestamp::now());
+
+    let (feed, error) = match Feed::from_xml(&xml) {
+        Ok(feed) => match feed.entry().validate() {
+            Ok(()) => (Some(feed), None),
+            Err(error) => (Some(feed), Some(error.to_string())),
+        },
+        Err(error) => (None, Some(error.to_string())),
+    };
+
+    let queued = match (&feed, &error) {
+        (Some(feed), None) => VideoQueue::new_video(
+            &database,
+            feed.entry().clone(),
+            None,
+            config.playlist_id_for(&channel_id),
+            config.video_age_minutes_threshold,
+        )
+        .await
+        .map_err(InternalServerError)?,
+        _ => false,
+    };
+
+    if queued {
+        tracing::trace!("notifying new video queue");
+        video_queue_notify.notify_one();
+    }
+
+    AuditLog::record_audit_event(
+        &database,
+        "feed.test",
+        &actor,
+        Some(&format!("channel:{channel_id}")),
+        Some(&format!("video_id={}, queued={queued}", upload.video_id)),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(Json(FeedTest {
+        xml,
+        feed,
+        error,
+        queued,
+    })
+    .into_response())
+}
+
+/// Atom feed in the format that the hub sends for new uploads
+fn synthetic_feed(channel_id: &str, upload: &LatestUpload, updated: Timestamp) -> String {
+    let LatestUpload {
+        video_id,
+        title,
+        published,
+    } = upload;
+    let title = escape(title.as_str());
+
+    format!(
+        r#"<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015"
+  xmlns="http://www.w3.org/2005/Atom">
+  <link rel="hub" href="https://pubsubhubbub.appspot.com" />
+  <link rel="self" href="https://www.youtube.com/xml/feeds/videos.xml?channel_id={channel_id}" />
+  <title>YouTube video feed</title>
+  <updated>{updated}</updated>
+  <entry>
+    <id>yt:video:{video_id}</id>
+    <yt:videoId>{video_id}</yt:videoId>
+    <yt:channelId>{channel_id}</yt:channelId>
+    <title>{title}</title>
+    <link rel="alternate" href="https://www.youtube.com/watch?v={video_id}" />
+    <author>
+      <name>{channel_id}</name>
+      <uri>https://www.youtube.com/channel/{channel_id}</uri>
+    </author>
+    <published>{published}</published>
+    <updated>{updated}</updated>
+  </entry>
+</feed>
+"#
+    )
+}
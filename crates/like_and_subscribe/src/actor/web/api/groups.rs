@@ -0,0 +1,140 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr, SqlErr};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actor::web::TailscaleUser,
+    database::{AuditLog, ChannelGroups},
+};
+
+#[derive(Debug, Serialize)]
+pub struct Group {
+    id: i32,
+    name: String,
+    channel_ids: Vec<String>,
+}
+
+pub async fn list(
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<Vec<Group>>, InternalServerError<DbErr>> {
+    let groups = ChannelGroups::get_all_with_members(&database)
+        .await
+        .map_err(InternalServerError)?;
+
+    Ok(Json(
+        groups
+            .into_iter()
+            .map(|(group, members)| Group {
+                id: group.id,
+                name: group.name,
+                channel_ids: members
+                    .into_iter()
+                    .map(|member| member.channel_id)
+                    .collect(),
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewGroup {
+    name: String,
+}
+
+pub async fn create(
+    TailscaleUser(actor): TailscaleUser,
+    State(database): State<DatabaseConnection>,
+    Json(new_group): Json<NewGroup>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    let group = match ChannelGroups::create(&database, new_group.name).await {
+        Ok(group) => group,
+        Err(error) if matches!(error.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+            return Ok(StatusCode::CONFLICT.into_response());
+        }
+        Err(error) => return Err(InternalServerError(error)),
+    };
+
+    AuditLog::record_audit_event(
+        &database,
+        "groups.create",
+        &actor,
+        Some(&format!("group:{}", group.id)),
+        Some(&group.name),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok((StatusCode::CREATED, Json(group)).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Members {
+    channel_ids: Vec<String>,
+}
+
+pub async fn set_members(
+    TailscaleUser(actor): TailscaleUser,
+    Path(group_id): Path<i32>,
+    State(database): State<DatabaseConnection>,
+    Json(members): Json<Members>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    let details = members.channel_ids.join(",");
+
+    match ChannelGroups::set_members(&database, group_id, members.channel_ids).await {
+        Ok(true) => {}
+        Ok(false) => return Ok(StatusCode::NOT_FOUND),
+        Err(error)
+            if matches!(
+                error.sql_err(),
+                Some(SqlErr::ForeignKeyConstraintViolation(_))
+            ) =>
+        {
+            return Ok(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+        Err(error) => return Err(InternalServerError(error)),
+    }
+
+    AuditLog::record_audit_event(
+        &database,
+        "groups.set_members",
+        &actor,
+        Some(&format!("group:{group_id}")),
+        Some(&details),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn remove_member(
+    TailscaleUser(actor): TailscaleUser,
+    Path((group_id, channel_id)): Path<(i32, String)>,
+    State(database): State<DatabaseConnection>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    let removed = ChannelGroups::remove_member(&database, group_id, channel_id.clone())
+        .await
+        .map_err(InternalServerError)?;
+
+    if !removed {
+        return Ok(StatusCode::NOT_FOUND);
+    }
+
+    AuditLog::record_audit_event(
+        &database,
+        "groups.remove_member",
+        &actor,
+        Some(&format!("group:{group_id}")),
+        Some(&channel_id),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
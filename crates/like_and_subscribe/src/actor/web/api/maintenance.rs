@@ -0,0 +1,58 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use axum::{Json, extract::State};
+use axum_extra::response::InternalServerError;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+
+use crate::{actor::web::TailscaleUser, database::AuditLog};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MaintenanceMode {
+    enabled: bool,
+}
+
+/// While enabled, new uploads are rejected so that the hub retries them later
+pub async fn set(
+    TailscaleUser(actor): TailscaleUser,
+    State((database, maintenance_mode)): State<(DatabaseConnection, Arc<AtomicBool>)>,
+    Json(MaintenanceMode { enabled }): Json<MaintenanceMode>,
+) -> Result<Json<MaintenanceMode>, InternalServerError<DbErr>> {
+    set_maintenance_mode(&database, &maintenance_mode, &actor, enabled).await
+}
+
+pub async fn disable(
+    TailscaleUser(actor): TailscaleUser,
+    State((database, maintenance_mode)): State<(DatabaseConnection, Arc<AtomicBool>)>,
+) -> Result<Json<MaintenanceMode>, InternalServerError<DbErr>> {
+    set_maintenance_mode(&database, &maintenance_mode, &actor, false).await
+}
+
+async fn set_maintenance_mode(
+    database: &DatabaseConnection,
+    maintenance_mode: &AtomicBool,
+    actor: &str,
+    enabled: bool,
+) -> Result<Json<MaintenanceMode>, InternalServerError<DbErr>> {
+    maintenance_mode.store(enabled, Ordering::Relaxed);
+    tracing::warn!(enabled, actor, "maintenance mode changed");
+
+    AuditLog::record_audit_event(
+        database,
+        if enabled {
+            "maintenance.enable"
+        } else {
+            "maintenance.disable"
+        },
+        actor,
+        None,
+        None,
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(Json(MaintenanceMode { enabled }))
+}
@@ -0,0 +1,131 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use entity::maintenance_windows;
+use jiff::Timestamp;
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Deserialize;
+
+use crate::{
+    actor::web::TailscaleUser,
+    database::{AuditLog, MaintenanceWindows},
+};
+
+/// Windows that are in progress or scheduled
+pub async fn list(
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<Vec<maintenance_windows::Model>>, InternalServerError<DbErr>> {
+    MaintenanceWindows::get_upcoming(&database, Timestamp::now())
+        .await
+        .map(Json)
+        .map_err(InternalServerError)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MaintenanceWindow {
+    starts_at: Timestamp,
+    ends_at: Timestamp,
+    reason: String,
+}
+
+impl MaintenanceWindow {
+    fn details(&self) -> String {
+        format!("{} to {}: {}", self.starts_at, self.ends_at, self.reason)
+    }
+}
+
+/// New uploads are rejected during the window so that the hub retries them once it has ended
+pub async fn create(
+    TailscaleUser(actor): TailscaleUser,
+    State(database): State<DatabaseConnection>,
+    Json(window): Json<MaintenanceWindow>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    if window.ends_at <= window.starts_at {
+        return Ok(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+    }
+
+    let details = window.details();
+    let created =
+        MaintenanceWindows::create(&database, window.starts_at, window.ends_at, window.reason)
+            .await
+            .map_err(InternalServerError)?;
+
+    AuditLog::record_audit_event(
+        &database,
+        "maintenance_windows.create",
+        &actor,
+        Some(&format!("maintenance_window:{}", created.id)),
+        Some(&details),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok((StatusCode::CREATED, Json(created)).into_response())
+}
+
+pub async fn update(
+    TailscaleUser(actor): TailscaleUser,
+    Path(id): Path<i64>,
+    State(database): State<DatabaseConnection>,
+    Json(window): Json<MaintenanceWindow>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    if window.ends_at <= window.starts_at {
+        return Ok(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+    }
+
+    let details = window.details();
+    let Some(updated) = MaintenanceWindows::update(
+        &database,
+        id,
+        window.starts_at,
+        window.ends_at,
+        window.reason,
+    )
+    .await
+    .map_err(InternalServerError)?
+    else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    AuditLog::record_audit_event(
+        &database,
+        "maintenance_windows.update",
+        &actor,
+        Some(&format!("maintenance_window:{id}")),
+        Some(&details),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(Json(updated).into_response())
+}
+
+pub async fn delete(
+    TailscaleUser(actor): TailscaleUser,
+    Path(id): Path<i64>,
+    State(database): State<DatabaseConnection>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    let deleted = MaintenanceWindows::delete(&database, id)
+        .await
+        .map_err(InternalServerError)?;
+
+    if !deleted {
+        return Ok(StatusCode::NOT_FOUND);
+    }
+
+    AuditLog::record_audit_event(
+        &database,
+        "maintenance_windows.delete",
+        &actor,
+        Some(&format!("maintenance_window:{id}")),
+        None,
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
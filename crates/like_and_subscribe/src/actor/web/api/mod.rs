@@ -0,0 +1,270 @@
+use std::{
+    sync::{Arc, atomic::AtomicBool},
+    time::Duration,
+};
+
+use axum::routing::method_routing;
+use axum_extra::routing::RouterExt as _;
+use sea_orm::DatabaseConnection;
+use tokio::sync::Notify;
+
+use crate::{config::Config, oauth::TokenManager, task_metrics::TaskMonitors};
+
+mod audit_log;
+mod channels;
+mod config;
+mod dashboard_data;
+mod duplicate_check;
+mod feed_test;
+mod groups;
+mod maintenance;
+mod maintenance_windows;
+mod playlist_sync;
+mod quota_usage;
+mod subscriptions;
+mod sync_history;
+mod task_metrics;
+mod test_webhook;
+mod timeline;
+mod video_blacklist;
+mod video_queue;
+mod video_stats;
+
+/// Give up on requests that use the youtube api if it cannot be authenticated within this long
+const TOKEN_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[expect(clippy::too_many_arguments)]
+pub fn router(
+    database: DatabaseConnection,
+    subscriptions_queue_notify: Arc<Notify>,
+    video_queue_notify: Arc<Notify>,
+    task_monitors: TaskMonitors,
+    config: Arc<Config>,
+    maintenance_mode: Arc<AtomicBool>,
+    client: reqwest::Client,
+    token_manager: TokenManager,
+) -> axum::Router {
+    axum::Router::new()
+        .route_with_tsr(
+            "/audit-log",
+            method_routing::get(audit_log::audit_log).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/bulk-unsubscribe-inactive",
+            method_routing::post(subscriptions::bulk_unsubscribe_inactive).with_state((
+                database.clone(),
+                subscriptions_queue_notify.clone(),
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/channels/{channel_id}",
+            method_routing::get(channels::channel)
+                .patch(channels::update)
+                .with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/channels/import",
+            method_routing::post(channels::import).with_state(channels::ImportState {
+                database: database.clone(),
+                notify: subscriptions_queue_notify.clone(),
+                client: client.clone(),
+                token_manager: token_manager.clone(),
+                config: config.clone(),
+            }),
+        )
+        .route_with_tsr(
+            "/channels/search",
+            method_routing::get(channels::search).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/channels/{channel_id}/pause",
+            method_routing::post(channels::pause).with_state((
+                database.clone(),
+                subscriptions_queue_notify.clone(),
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/channels/{channel_id}/resubscribe",
+            method_routing::post(channels::resubscribe).with_state((
+                database.clone(),
+                subscriptions_queue_notify.clone(),
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/channels/{channel_id}/unsubscribe",
+            method_routing::post(channels::unsubscribe).with_state((
+                database.clone(),
+                subscriptions_queue_notify.clone(),
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/channels/{channel_id}/videos",
+            method_routing::get(channels::videos).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/config",
+            method_routing::get(config::config).with_state(config.clone()),
+        )
+        .route_with_tsr(
+            "/dashboard-data",
+            method_routing::get(dashboard_data::dashboard_data)
+                .with_state((database.clone(), config.clone())),
+        )
+        .route_with_tsr(
+            "/dead-letters/{id}/requeue",
+            method_routing::post(subscriptions::requeue_dead_letter).with_state((
+                database.clone(),
+                subscriptions_queue_notify.clone(),
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/duplicate-check/{video_id}",
+            method_routing::get(duplicate_check::duplicate_check).with_state((
+                database.clone(),
+                client.clone(),
+                token_manager.clone(),
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/feed-test/{channel_id}",
+            method_routing::post(feed_test::feed_test).with_state(feed_test::FeedTestState {
+                database: database.clone(),
+                video_queue_notify,
+                client: client.clone(),
+                token_manager: token_manager.clone(),
+                config: config.clone(),
+            }),
+        )
+        .route_with_tsr(
+            "/groups",
+            method_routing::get(groups::list)
+                .post(groups::create)
+                .with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/groups/{group_id}/members",
+            method_routing::put(groups::set_members).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/groups/{group_id}/members/{channel_id}",
+            method_routing::delete(groups::remove_member).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/maintenance",
+            method_routing::post(maintenance::set)
+                .delete(maintenance::disable)
+                .with_state((database.clone(), maintenance_mode)),
+        )
+        .route_with_tsr(
+            "/maintenance-windows",
+            method_routing::get(maintenance_windows::list)
+                .post(maintenance_windows::create)
+                .with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/maintenance-windows/{id}",
+            method_routing::put(maintenance_windows::update)
+                .delete(maintenance_windows::delete)
+                .with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/playlist-sync",
+            method_routing::get(playlist_sync::playlist_sync).with_state((
+                database.clone(),
+                client.clone(),
+                token_manager,
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/quota-usage",
+            method_routing::get(quota_usage::quota_usage).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/resubscribe-all",
+            method_routing::post(subscriptions::resubscribe_all).with_state((
+                database.clone(),
+                subscriptions_queue_notify.clone(),
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/subscription-status",
+            method_routing::get(subscriptions::status)
+                .with_state((database.clone(), config.clone())),
+        )
+        .route_with_tsr(
+            "/subscription-stats",
+            method_routing::get(subscriptions::stats).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/subscriptions/dead-letters",
+            method_routing::get(subscriptions::dead_letters)
+                .with_state((database.clone(), config.clone())),
+        )
+        .route_with_tsr(
+            "/subscriptions/dead-letters/{id}/requeue",
+            method_routing::post(subscriptions::requeue_dead_letter).with_state((
+                database.clone(),
+                subscriptions_queue_notify,
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/subscriptions/{channel_id}/verify",
+            method_routing::get(subscriptions::verify).with_state((
+                database.clone(),
+                client.clone(),
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/sync-history",
+            method_routing::get(sync_history::sync_history).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/task-metrics",
+            method_routing::get(task_metrics::task_metrics).with_state(task_monitors),
+        )
+        .route_with_tsr(
+            "/test-webhook",
+            method_routing::post(test_webhook::test_webhook).with_state((
+                database.clone(),
+                client,
+                config.clone(),
+            )),
+        )
+        .route_with_tsr(
+            "/timeline",
+            method_routing::get(timeline::timeline)
+                .with_state((database.clone(), timeline::TimelineCache::default())),
+        )
+        .route_with_tsr(
+            "/video-blacklist",
+            method_routing::get(video_blacklist::list)
+                .post(video_blacklist::add)
+                .with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/video-blacklist/{video_id}",
+            method_routing::delete(video_blacklist::remove).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/video-queue",
+            method_routing::get(video_queue::video_queue).with_state(database.clone()),
+        )
+        .route_with_tsr(
+            "/video-queue-results/old",
+            method_routing::delete(video_queue::delete_old).with_state((database.clone(), config)),
+        )
+        .route_with_tsr(
+            "/video-stats",
+            method_routing::get(video_stats::video_stats).with_state(database),
+        )
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Deserialize;
+
+use super::TOKEN_WAIT_TIMEOUT;
+use crate::{
+    actor::playlist::PlaylistDiff, config::Config, database::VideoQueue, oauth::TokenManager,
+    playlist,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct PlaylistSyncParams {
+    /// Defaults to the fallback playlist
+    playlist_id: Option<String>,
+}
+
+/// Compare the playlist on youtube against the videos that have been inserted into it
+pub async fn playlist_sync(
+    Query(params): Query<PlaylistSyncParams>,
+    State((database, client, token_manager, config)): State<(
+        DatabaseConnection,
+        reqwest::Client,
+        TokenManager,
+        Arc<Config>,
+    )>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    let playlist_id = params
+        .playlist_id
+        .as_deref()
+        .unwrap_or_else(|| config.default_playlist_id());
+
+    if !config.playlist_ids().contains(playlist_id) {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    let token = match tokio::time::timeout(TOKEN_WAIT_TIMEOUT, token_manager.wait_for_token()).await
+    {
+        Ok(token) => token.map_err(InternalServerError)?,
+        Err(_) => {
+            tracing::warn!("timed out waiting for a token to sync the playlist");
+            return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response());
+        }
+    };
+
+    let videos = match playlist::list_videos(&database, &client, &token, playlist_id).await {
+        Ok(videos) => videos,
+        Err(error) => {
+            tracing::error!(%error, "failed to list playlist videos");
+            return Ok(StatusCode::BAD_GATEWAY.into_response());
+        }
+    };
+
+    let inserted =
+        VideoQueue::get_inserted_video_ids(&database, playlist_id, config.default_playlist_id())
+            .await
+            .map_err(InternalServerError)?;
+
+    Ok(Json(PlaylistDiff::new(&videos, &inserted)).into_response())
+}
@@ -0,0 +1,70 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use jiff::{Timestamp, civil::Date};
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+
+use crate::{database::YoutubeApiCalls, quota::quota_time_zone};
+
+#[derive(Debug, Deserialize)]
+pub struct QuotaUsageParams {
+    /// Defaults to today, in the Pacific time zone used for the quota reset
+    date: Option<Date>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuotaUsage {
+    date: Date,
+    calls: i64,
+    quota_cost: i64,
+    endpoints: Vec<EndpointUsage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EndpointUsage {
+    endpoint: String,
+    calls: i64,
+    quota_cost: i64,
+}
+
+pub async fn quota_usage(
+    Query(params): Query<QuotaUsageParams>,
+    State(database): State<DatabaseConnection>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    let time_zone = quota_time_zone();
+
+    let date = params
+        .date
+        .unwrap_or_else(|| Timestamp::now().to_zoned(time_zone.clone()).date());
+
+    let Ok((since, until)) = date
+        .to_zoned(time_zone)
+        .and_then(|start| Ok((start.timestamp(), start.tomorrow()?.timestamp())))
+    else {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    };
+
+    let endpoints = YoutubeApiCalls::get_usage(&database, since, until)
+        .await
+        .map_err(InternalServerError)?
+        .into_iter()
+        .map(|(endpoint, calls, quota_cost)| EndpointUsage {
+            endpoint,
+            calls,
+            quota_cost,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Json(QuotaUsage {
+        date,
+        calls: endpoints.iter().map(|usage| usage.calls).sum(),
+        quota_cost: endpoints.iter().map(|usage| usage.quota_cost).sum(),
+        endpoints,
+    })
+    .into_response())
+}
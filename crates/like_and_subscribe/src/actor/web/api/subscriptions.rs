@@ -0,0 +1,504 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use entity::subscription_queue;
+use entity_types::subscription_queue::{SubscriptionAction, SubscriptionSource};
+use jiff::{SignedDuration, Timestamp};
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::{
+    actor::web::TailscaleUser,
+    config::Config,
+    database::{
+        ActiveSubscriptions, AuditLog, ChannelGroups, KnownChannels, SubscriptionQueue,
+        SubscriptionStats,
+    },
+};
+
+#[cfg(test)]
+mod test {
+    use jiff::Timestamp;
+
+    use crate::actor::web::api::subscriptions::HubSubscriptionDetails;
+
+    #[test]
+    fn parse_subscription_details() {
+        let html = r#"<html><body><h1>Subscription details</h1><dl>
+            <dt>Callback URL:</dt><dd><a href="https://example.com/pubsub">https://example.com/pubsub</a></dd>
+            <dt>State:</dt><dd>verified</dd>
+            <dt>Expiration time:</dt><dd>Fri, 12 Sep 2025 10:23:19 +0000</dd>
+            <dt>Last delivery error:</dt><dd></dd>
+        </dl></body></html>"#;
+
+        let details = HubSubscriptionDetails::parse(html);
+
+        assert_eq!(details.state.as_deref(), Some("verified"));
+        assert_eq!(
+            details.expiration,
+            Some("2025-09-12T10:23:19Z".parse::<Timestamp>().unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_unknown_subscription() {
+        let details = HubSubscriptionDetails::parse("<html><body>Not found</body></html>");
+
+        assert_eq!(details.state, None);
+        assert_eq!(details.expiration, None);
+    }
+}
+
+/// Subscriptions expiring within this long are reported as expiring
+const EXPIRING_WINDOW: SignedDuration = SignedDuration::from_hours(24);
+
+/// Difference allowed between the hub's lease end and the local expiration, which are
+/// recorded at slightly different times
+const LEASE_END_TOLERANCE: SignedDuration = SignedDuration::from_hours(1);
+
+const HUB_SUBSCRIPTION_DETAILS_URL: &str = "https://pubsubhubbub.appspot.com/subscription-details";
+
+/// Refuse to queue a bulk resubscription while this many subscribe actions
+/// are still waiting to be processed
+const MAX_PENDING_SUBSCRIBE_ACTIONS: u64 = 50;
+
+const MAX_PAGE_SIZE: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ResubscribeParams {
+    /// Only resubscribe to the channels in this group
+    group_filter: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Queued {
+    queued: usize,
+}
+
+pub async fn resubscribe_all(
+    TailscaleUser(actor): TailscaleUser,
+    Query(params): Query<ResubscribeParams>,
+    State((database, notify, config)): State<(DatabaseConnection, Arc<Notify>, Arc<Config>)>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    let pending = SubscriptionQueue::count_pending(&database, SubscriptionAction::Subscribe)
+        .await
+        .map_err(InternalServerError)?;
+
+    if pending > MAX_PENDING_SUBSCRIBE_ACTIONS {
+        tracing::warn!(pending, "refusing to flood the subscription queue");
+        return Ok(StatusCode::TOO_MANY_REQUESTS.into_response());
+    }
+
+    let channel_ids = match params.group_filter {
+        Some(group_id) => match ChannelGroups::get_member_channel_ids(&database, group_id)
+            .await
+            .map_err(InternalServerError)?
+        {
+            Some(channel_ids) => channel_ids,
+            None => return Ok(StatusCode::NOT_FOUND.into_response()),
+        },
+        None => KnownChannels::get_all_channel_ids(&database)
+            .await
+            .map_err(InternalServerError)?,
+    };
+    let queued = channel_ids.len();
+
+    match SubscriptionQueue::add_actions(
+        &database,
+        &notify,
+        config.subscription_queue_max_rows,
+        config.subscription_max_retries,
+        channel_ids
+            .into_iter()
+            .map(|channel_id| (channel_id, SubscriptionAction::Subscribe)),
+        SubscriptionSource::AdminManual,
+    )
+    .await
+    {
+        Ok(()) => {}
+        Err(error) if SubscriptionQueue::is_queue_full(&error) => {
+            return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response());
+        }
+        Err(error) => return Err(InternalServerError(error)),
+    }
+
+    AuditLog::record_audit_event(
+        &database,
+        "subscriptions.resubscribe_all",
+        &actor,
+        params
+            .group_filter
+            .map(|group_id| format!("group:{group_id}"))
+            .as_deref(),
+        Some(&format!("queued {queued} subscribe actions")),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(Json(Queued { queued }).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkUnsubscribeParams {
+    /// List the inactive channels without queueing any unsubscribes
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InactiveChannel {
+    channel_id: String,
+    channel_name: String,
+    last_video_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkUnsubscribed {
+    unsubscribed_count: usize,
+    channels: Vec<InactiveChannel>,
+}
+
+/// Unsubscribe from every subscribed channel that has not uploaded within the inactivity threshold
+pub async fn bulk_unsubscribe_inactive(
+    TailscaleUser(actor): TailscaleUser,
+    Query(params): Query<BulkUnsubscribeParams>,
+    State((database, notify, config)): State<(DatabaseConnection, Arc<Notify>, Arc<Config>)>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    let channels = KnownChannels::get_inactive_subscribed(
+        &database,
+        Timestamp::now() - config.inactive_channel_threshold(),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    let channels = channels
+        .into_iter()
+        .map(|channel| InactiveChannel {
+            channel_id: channel.channel_id,
+            channel_name: channel.channel_name,
+            last_video_at: channel.last_video_at.map(|last_video_at| last_video_at.0),
+        })
+        .collect::<Vec<_>>();
+
+    if params.dry_run || channels.is_empty() {
+        return Ok(Json(BulkUnsubscribed {
+            unsubscribed_count: 0,
+            channels,
+        })
+        .into_response());
+    }
+
+    let unsubscribed_count = channels.len();
+
+    match SubscriptionQueue::add_actions(
+        &database,
+        &notify,
+        config.subscription_queue_max_rows,
+        config.subscription_max_retries,
+        channels
+            .iter()
+            .map(|channel| (channel.channel_id.clone(), SubscriptionAction::Unsubscribe)),
+        SubscriptionSource::AdminManual,
+    )
+    .await
+    {
+        Ok(()) => {}
+        Err(error) if SubscriptionQueue::is_queue_full(&error) => {
+            return Ok(StatusCode::SERVICE_UNAVAILABLE.into_response());
+        }
+        Err(error) => return Err(InternalServerError(error)),
+    }
+
+    AuditLog::record_audit_event(
+        &database,
+        "subscriptions.bulk_unsubscribe_inactive",
+        &actor,
+        None,
+        Some(&format!(
+            "queued {unsubscribed_count} unsubscribe actions for channels inactive for {} days",
+            config.inactive_channel_days
+        )),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(Json(BulkUnsubscribed {
+        unsubscribed_count,
+        channels,
+    })
+    .into_response())
+}
+
+/// Subscription actions that have been given up on after failing too many times
+pub async fn dead_letters(
+    State((database, config)): State<(DatabaseConnection, Arc<Config>)>,
+) -> Result<Json<Vec<subscription_queue::Model>>, InternalServerError<DbErr>> {
+    SubscriptionQueue::get_dead_letters(&database, config.subscription_max_retries)
+        .await
+        .map(Json)
+        .map_err(InternalServerError)
+}
+
+pub async fn stats(
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<SubscriptionStats>, InternalServerError<DbErr>> {
+    ActiveSubscriptions::get_subscription_stats(&database)
+        .await
+        .map(Json)
+        .map_err(InternalServerError)
+}
+
+pub async fn requeue_dead_letter(
+    TailscaleUser(actor): TailscaleUser,
+    Path(id): Path<i64>,
+    State((database, notify, config)): State<(DatabaseConnection, Arc<Notify>, Arc<Config>)>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    // Actions that are still being retried are left alone, so that they are not queued twice
+    if !SubscriptionQueue::is_dead_letter(&database, id, config.subscription_max_retries)
+        .await
+        .map_err(InternalServerError)?
+    {
+        return Ok(StatusCode::NOT_FOUND);
+    }
+
+    match SubscriptionQueue::check_capacity(
+        &database,
+        config.subscription_queue_max_rows,
+        config.subscription_max_retries,
+        1,
+    )
+    .await
+    {
+        Ok(_) => {}
+        Err(error) if SubscriptionQueue::is_queue_full(&error) => {
+            return Ok(StatusCode::SERVICE_UNAVAILABLE);
+        }
+        Err(error) => return Err(InternalServerError(error)),
+    }
+
+    match SubscriptionQueue::requeue_dead_letter(&database, &notify, id).await {
+        Ok(()) => {}
+        Err(DbErr::RecordNotFound(_)) => return Ok(StatusCode::NOT_FOUND),
+        Err(error) => return Err(InternalServerError(error)),
+    }
+
+    AuditLog::record_audit_event(
+        &database,
+        "subscriptions.requeue_dead_letter",
+        &actor,
+        Some(&format!("subscription_queue:{id}")),
+        None,
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusParams {
+    #[serde(default = "default_page")]
+    page: u64,
+    #[serde(default = "default_page_size")]
+    page_size: u64,
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_page_size() -> u64 {
+    100
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    Active,
+    Pending,
+    Unsubscribed,
+    Expiring,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChannelSubscriptionStatus {
+    channel_id: String,
+    channel_name: String,
+    subscription_status: SubscriptionStatus,
+    expiration_timestamp: Option<Timestamp>,
+    days_until_expiry: Option<i64>,
+    last_notified_at: Option<Timestamp>,
+    pending_actions_count: i64,
+}
+
+pub async fn status(
+    Query(params): Query<StatusParams>,
+    State((database, config)): State<(DatabaseConnection, Arc<Config>)>,
+) -> Result<Json<Vec<ChannelSubscriptionStatus>>, InternalServerError<DbErr>> {
+    let page_size = params.page_size.min(MAX_PAGE_SIZE);
+
+    let channels = KnownChannels::get_subscription_statuses(
+        &database,
+        config.subscription_max_retries,
+        page_size,
+        params.page.saturating_sub(1).saturating_mul(page_size),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    let now = Timestamp::now();
+
+    Ok(Json(
+        channels
+            .into_iter()
+            .map(|channel| {
+                let until_expiry = channel
+                    .expiration
+                    .map(|expiration| now.duration_until(expiration));
+
+                let subscription_status = match until_expiry {
+                    Some(until_expiry) if until_expiry > EXPIRING_WINDOW => {
+                        SubscriptionStatus::Active
+                    }
+                    Some(until_expiry) if until_expiry > SignedDuration::ZERO => {
+                        SubscriptionStatus::Expiring
+                    }
+                    _ if channel.pending_actions_count > 0 => SubscriptionStatus::Pending,
+                    _ => SubscriptionStatus::Unsubscribed,
+                };
+
+                ChannelSubscriptionStatus {
+                    channel_id: channel.channel_id,
+                    channel_name: channel.channel_name,
+                    subscription_status,
+                    expiration_timestamp: channel.expiration,
+                    days_until_expiry: until_expiry
+                        .map(|until_expiry| until_expiry.as_secs() / (60 * 60 * 24)),
+                    last_notified_at: channel.last_video_at,
+                    pending_actions_count: channel.pending_actions_count,
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// Fields scraped from the hub's subscription details page
+#[derive(Debug)]
+struct HubSubscriptionDetails {
+    state: Option<String>,
+    expiration: Option<Timestamp>,
+}
+
+impl HubSubscriptionDetails {
+    /// The page is only meant for humans, so each value is taken from the text after its label
+    fn parse(html: &str) -> Self {
+        let text = html
+            .split('<')
+            .filter_map(|node| node.split_once('>').map(|(_tag, text)| text.trim()))
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>();
+
+        let value = |label: &str| {
+            text.windows(2)
+                .find(|pair| pair[0].trim_end_matches(':').eq_ignore_ascii_case(label))
+                .map(|pair| pair[1])
+        };
+
+        HubSubscriptionDetails {
+            state: value("State").map(str::to_owned),
+            expiration: value("Expiration time").and_then(|expiration| {
+                expiration.parse::<Timestamp>().ok().or_else(|| {
+                    jiff::fmt::rfc2822::parse(expiration)
+                        .ok()
+                        .map(|zoned| zoned.timestamp())
+                })
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HubVerification {
+    /// State reported by the hub, such as `verified`, missing if the hub does not know the subscription
+    status: Option<String>,
+    lease_end: Option<Timestamp>,
+    /// Expiration of the local subscription, missing if there is none
+    local_expiration: Option<Timestamp>,
+    /// Whether the hub and the local subscription agree that the channel is subscribed
+    matches: bool,
+}
+
+pub async fn verify(
+    Path(channel_id): Path<String>,
+    State((database, client, config)): State<(DatabaseConnection, reqwest::Client, Arc<Config>)>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    if KnownChannels::get_channel(&database, &channel_id)
+        .await
+        .map_err(InternalServerError)?
+        .is_none()
+    {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    let local = ActiveSubscriptions::get_subscription(&database, &channel_id)
+        .await
+        .map_err(InternalServerError)?;
+
+    let topic = format!("https://www.youtube.com/xml/feeds/videos.xml?channel_id={channel_id}");
+    let callback = config.pubsub_callback();
+    let secret = local
+        .as_ref()
+        .and_then(|local| local.secret.as_deref())
+        .unwrap_or_default();
+
+    let response = client
+        .get(HUB_SUBSCRIPTION_DETAILS_URL)
+        .query(&[
+            ("hub.callback", callback.as_str()),
+            ("hub.topic", topic.as_str()),
+            ("hub.secret", secret),
+        ])
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+
+    let html = match response {
+        Ok(response) => response.text().await,
+        Err(error) => Err(error),
+    };
+
+    let details = match html {
+        Ok(html) => HubSubscriptionDetails::parse(&html),
+        Err(error) => {
+            tracing::warn!(%error, channel_id, "failed to fetch subscription details from the hub");
+            return Ok(StatusCode::BAD_GATEWAY.into_response());
+        }
+    };
+
+    let local_expiration = local.map(|local| local.expiration.0);
+
+    let matches = match (details.state.as_deref(), local_expiration) {
+        (Some("verified"), Some(local_expiration)) => details.expiration.is_none_or(|lease_end| {
+            lease_end.duration_since(local_expiration).abs() <= LEASE_END_TOLERANCE
+        }),
+        (Some("verified"), None) => false,
+        // Neither side thinks the channel is subscribed
+        (_, None) => true,
+        (_, Some(_)) => false,
+    };
+
+    Ok(Json(HubVerification {
+        status: details.state,
+        lease_end: details.expiration,
+        local_expiration,
+        matches,
+    })
+    .into_response())
+}
@@ -0,0 +1,17 @@
+use axum::{Json, extract::State};
+use axum_extra::response::InternalServerError;
+use entity::subscription_sync_runs;
+use sea_orm::{DatabaseConnection, DbErr};
+
+use crate::database::SubscriptionSyncRuns;
+
+const SYNC_HISTORY_LIMIT: u64 = 10;
+
+pub async fn sync_history(
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<Vec<subscription_sync_runs::Model>>, InternalServerError<DbErr>> {
+    SubscriptionSyncRuns::get_recent(&database, SYNC_HISTORY_LIMIT)
+        .await
+        .map(Json)
+        .map_err(InternalServerError)
+}
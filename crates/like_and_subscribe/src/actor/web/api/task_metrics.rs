@@ -0,0 +1,29 @@
+use axum::{Json, extract::State};
+use serde::Serialize;
+
+use crate::task_metrics::TaskMonitors;
+
+#[derive(Debug, Serialize)]
+pub struct TaskMetrics {
+    name: &'static str,
+    poll_count: u64,
+    slow_poll_count: u64,
+    mean_poll_duration_ms: f64,
+    slow_poll_percentage: f64,
+}
+
+pub async fn task_metrics(State(monitors): State<TaskMonitors>) -> Json<Vec<TaskMetrics>> {
+    Json(
+        monitors
+            .metrics()
+            .into_iter()
+            .map(|metrics| TaskMetrics {
+                name: metrics.name,
+                poll_count: metrics.poll_count,
+                slow_poll_count: metrics.slow_poll_count,
+                mean_poll_duration_ms: metrics.mean_poll_duration().as_secs_f64() * 1000.0,
+                slow_poll_percentage: metrics.slow_poll_percentage(),
+            })
+            .collect(),
+    )
+}
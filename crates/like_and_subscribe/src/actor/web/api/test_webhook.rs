@@ -0,0 +1,87 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Json,
+    extract::State,
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use jiff::Timestamp;
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Serialize;
+
+use crate::{actor::web::TailscaleUser, config::Config, database::AuditLog};
+
+/// Give up on the webhook if it has not responded within this long
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize)]
+struct TestPayload {
+    event: &'static str,
+    timestamp: Timestamp,
+    version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestWebhook {
+    status: u16,
+    body: String,
+}
+
+/// Send a test event to the configured webhook, returning how it responded
+pub async fn test_webhook(
+    TailscaleUser(actor): TailscaleUser,
+    State((database, client, config)): State<(DatabaseConnection, reqwest::Client, Arc<Config>)>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    let Some(webhook_url) = &config.webhook_url else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let result = client
+        .post(webhook_url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(&TestPayload {
+            event: "test",
+            timestamp: Timestamp::now(),
+            version: "1",
+        })
+        .send()
+        .await;
+
+    AuditLog::record_audit_event(
+        &database,
+        "webhook.test",
+        &actor,
+        None,
+        Some(&match &result {
+            Ok(response) => format!("webhook responded with {}", response.status()),
+            Err(error) => format!("failed to send test event: {error}"),
+        }),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(error) if error.is_timeout() => {
+            tracing::warn!(%error, "timed out sending test event to webhook");
+            return Ok(StatusCode::GATEWAY_TIMEOUT.into_response());
+        }
+        Err(error) => {
+            tracing::warn!(%error, "failed to send test event to webhook");
+            return Ok(StatusCode::BAD_GATEWAY.into_response());
+        }
+    };
+
+    let status = response.status().as_u16();
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(error) => {
+            tracing::warn!(%error, "failed to read webhook response");
+            return Ok(StatusCode::BAD_GATEWAY.into_response());
+        }
+    };
+
+    Ok(Json(TestWebhook { status, body }).into_response())
+}
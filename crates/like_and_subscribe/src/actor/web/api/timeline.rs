@@ -0,0 +1,124 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::{IntoResponse as _, Response},
+};
+use axum_extra::response::InternalServerError;
+use entity_types::subscription_queue::SubscriptionAction;
+use jiff::{SignedDuration, Timestamp};
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::database::SubscriptionQueue;
+
+const CACHE_DURATION: Duration = Duration::from_secs(60);
+/// Longest timeline that can be requested, which also bounds the number of cached timelines
+const MAX_TIMELINE_DAYS: u32 = 366;
+
+#[derive(Debug, Deserialize)]
+pub struct TimelineParams {
+    #[serde(default = "default_timeline_days")]
+    days: u32,
+}
+
+fn default_timeline_days() -> u32 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineDay {
+    date: String,
+    subscribe: i64,
+    unsubscribe: i64,
+    refresh: i64,
+}
+
+struct CachedTimeline {
+    computed_at: Instant,
+    timeline: Vec<TimelineDay>,
+}
+
+/// Recently computed timelines, keyed by the number of days they cover
+#[derive(Clone, Default)]
+pub struct TimelineCache {
+    timelines: Arc<Mutex<HashMap<u32, CachedTimeline>>>,
+}
+
+pub async fn timeline(
+    Query(params): Query<TimelineParams>,
+    State((database, cache)): State<(DatabaseConnection, TimelineCache)>,
+) -> Result<Response, InternalServerError<DbErr>> {
+    if params.days > MAX_TIMELINE_DAYS {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            format!("days must be at most {MAX_TIMELINE_DAYS}"),
+        )
+            .into_response());
+    }
+
+    if let Some(CachedTimeline {
+        computed_at,
+        timeline,
+    }) = cache
+        .timelines
+        .lock()
+        .expect("mutex should not be poisoned")
+        .get(&params.days)
+        && computed_at.elapsed() < CACHE_DURATION
+    {
+        return Ok(Json(timeline.clone()).into_response());
+    }
+
+    let since = Timestamp::now() - SignedDuration::from_hours(24 * i64::from(params.days));
+
+    let counts = SubscriptionQueue::get_daily_action_counts(&database, since)
+        .await
+        .map_err(InternalServerError)?;
+
+    let mut timeline = Vec::<TimelineDay>::new();
+
+    for (date, action, count) in counts {
+        if timeline.last().is_none_or(|day| day.date != date) {
+            timeline.push(TimelineDay {
+                date,
+                subscribe: 0,
+                unsubscribe: 0,
+                refresh: 0,
+            });
+        }
+
+        let day = timeline.last_mut().expect("timeline should not be empty");
+
+        match action {
+            SubscriptionAction::Subscribe | SubscriptionAction::ForceSubscribe => {
+                day.subscribe += count
+            }
+            SubscriptionAction::Unsubscribe => day.unsubscribe = count,
+            SubscriptionAction::Refresh => day.refresh = count,
+            // Not related to the hub subscription
+            SubscriptionAction::PauseNotifications | SubscriptionAction::ResumeNotifications => {}
+        }
+    }
+
+    cache
+        .timelines
+        .lock()
+        .expect("mutex should not be poisoned")
+        .insert(
+            params.days,
+            CachedTimeline {
+                computed_at: Instant::now(),
+                timeline: timeline.clone(),
+            },
+        );
+
+    Ok(Json(timeline).into_response())
+}
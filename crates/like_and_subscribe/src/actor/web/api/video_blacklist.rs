@@ -0,0 +1,97 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use axum_extra::response::InternalServerError;
+use entity::video_blacklist;
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Deserialize;
+
+use crate::{
+    actor::web::TailscaleUser,
+    database::{AuditLog, VideoBlacklist},
+    feed::is_valid_video_id,
+};
+
+const MAX_LIST_LIMIT: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    #[serde(default = "default_list_limit")]
+    limit: u64,
+    #[serde(default)]
+    offset: u64,
+}
+
+fn default_list_limit() -> u64 {
+    50
+}
+
+pub async fn list(
+    Query(params): Query<ListParams>,
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<Vec<video_blacklist::Model>>, InternalServerError<DbErr>> {
+    VideoBlacklist::get_page(&database, params.limit.min(MAX_LIST_LIMIT), params.offset)
+        .await
+        .map(Json)
+        .map_err(InternalServerError)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewEntry {
+    video_id: String,
+    reason: String,
+}
+
+pub async fn add(
+    TailscaleUser(actor): TailscaleUser,
+    State(database): State<DatabaseConnection>,
+    Json(entry): Json<NewEntry>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    if !is_valid_video_id(&entry.video_id) {
+        return Ok(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    VideoBlacklist::add(&database, &entry.video_id, &entry.reason, &actor)
+        .await
+        .map_err(InternalServerError)?;
+
+    AuditLog::record_audit_event(
+        &database,
+        "video_blacklist.add",
+        &actor,
+        Some(&format!("video:{}", entry.video_id)),
+        Some(&entry.reason),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(StatusCode::CREATED)
+}
+
+pub async fn remove(
+    TailscaleUser(actor): TailscaleUser,
+    Path(video_id): Path<String>,
+    State(database): State<DatabaseConnection>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    let removed = VideoBlacklist::remove(&database, &video_id)
+        .await
+        .map_err(InternalServerError)?;
+
+    if !removed {
+        return Ok(StatusCode::NOT_FOUND);
+    }
+
+    AuditLog::record_audit_event(
+        &database,
+        "video_blacklist.remove",
+        &actor,
+        Some(&format!("video:{video_id}")),
+        None,
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
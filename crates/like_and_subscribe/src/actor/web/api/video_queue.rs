@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use axum_extra::response::InternalServerError;
+use entity::{video_queue, video_queue_result};
+use jiff::{SignedDuration, Timestamp};
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actor::web::TailscaleUser,
+    config::Config,
+    database::{AuditLog, VideoQueue, VideoQueueStatus},
+};
+
+const MAX_PAGE_SIZE: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct VideoQueueParams {
+    #[serde(default = "default_page")]
+    page: u64,
+    #[serde(default = "default_page_size")]
+    page_size: u64,
+    status: Option<VideoQueueStatus>,
+    channel_id: Option<String>,
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_page_size() -> u64 {
+    50
+}
+
+#[derive(Debug, Serialize)]
+pub struct VideoQueueItem {
+    #[serde(flatten)]
+    video: video_queue::Model,
+    /// [`None`] if the video has not been processed yet
+    result: Option<video_queue_result::Model>,
+}
+
+pub async fn video_queue(
+    Query(params): Query<VideoQueueParams>,
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<Vec<VideoQueueItem>>, InternalServerError<DbErr>> {
+    let page_size = params.page_size.min(MAX_PAGE_SIZE);
+
+    let videos = VideoQueue::get_page(
+        &database,
+        params.status,
+        params.channel_id.as_deref(),
+        page_size,
+        params.page.saturating_sub(1).saturating_mul(page_size),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(Json(
+        videos
+            .into_iter()
+            .map(|(video, result)| VideoQueueItem { video, result })
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteOldParams {
+    /// Defaults to the configured retention period
+    older_than_days: Option<u32>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeletedOld {
+    /// Number of videos that would be deleted, if this was a dry run
+    deleted_count: u64,
+}
+
+/// Delete processed videos, and their results, that were queued more than `older_than_days` ago
+pub async fn delete_old(
+    TailscaleUser(actor): TailscaleUser,
+    Query(params): Query<DeleteOldParams>,
+    State((database, config)): State<(DatabaseConnection, Arc<Config>)>,
+) -> Result<Json<DeletedOld>, InternalServerError<DbErr>> {
+    let older_than_days = params
+        .older_than_days
+        .unwrap_or(config.video_queue_retention_days);
+    let before = Timestamp::now() - SignedDuration::from_hours(24 * i64::from(older_than_days));
+
+    if params.dry_run {
+        return VideoQueue::count_processed_before(&database, before)
+            .await
+            .map(|deleted_count| Json(DeletedOld { deleted_count }))
+            .map_err(InternalServerError);
+    }
+
+    let deleted_count = VideoQueue::delete_processed_before(&database, before)
+        .await
+        .map_err(InternalServerError)?;
+
+    AuditLog::record_audit_event(
+        &database,
+        "video_queue.delete_old",
+        &actor,
+        None,
+        Some(&format!(
+            "deleted {deleted_count} videos older than {older_than_days} days"
+        )),
+    )
+    .await
+    .map_err(InternalServerError)?;
+
+    Ok(Json(DeletedOld { deleted_count }))
+}
@@ -0,0 +1,33 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use axum_extra::response::InternalServerError;
+use jiff::{SignedDuration, Timestamp};
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Deserialize;
+
+use crate::database::{VideoProcessingStats, VideoQueue};
+
+#[derive(Debug, Deserialize)]
+pub struct VideoStatsParams {
+    #[serde(default = "default_since_hours")]
+    since_hours: u32,
+}
+
+fn default_since_hours() -> u32 {
+    24
+}
+
+pub async fn video_stats(
+    Query(params): Query<VideoStatsParams>,
+    State(database): State<DatabaseConnection>,
+) -> Result<Json<VideoProcessingStats>, InternalServerError<DbErr>> {
+    let since = Timestamp::now() - SignedDuration::from_hours(params.since_hours.into());
+
+    Ok(Json(
+        VideoQueue::get_processing_stats(&database, since)
+            .await
+            .map_err(InternalServerError)?,
+    ))
+}
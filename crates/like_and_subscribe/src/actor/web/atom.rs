@@ -0,0 +1,89 @@
+use axum::{
+    extract::{FromRequest, Request, rejection::StringRejection},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use mime::Mime;
+use quick_xml::DeError;
+use reqwest::StatusCode;
+
+use crate::feed::Feed;
+
+/// Types that can be read from an atom feed
+pub trait FromAtomXml: Sized {
+    fn from_atom_xml(xml: &str) -> Result<Self, DeError>;
+}
+
+impl FromAtomXml for Feed {
+    /// Items that parse but fail validation are reported as [`DeError::Custom`]
+    fn from_atom_xml(xml: &str) -> Result<Self, DeError> {
+        let feed = Feed::from_xml(xml)?;
+
+        feed.entry()
+            .validate()
+            .map_err(|error| DeError::Custom(error.to_string()))?;
+
+        Ok(feed)
+    }
+}
+
+/// Request body with an `application/atom+xml` content type, parsed into `T`
+///
+/// The raw body is kept alongside so that its signature can be checked
+pub struct AtomXmlBody<T>(pub T, pub String);
+
+#[derive(Debug)]
+pub enum AtomXmlRejection {
+    UnsupportedMediaType,
+    Body(StringRejection),
+    /// The body is kept so that the failed feed can be recorded
+    Xml {
+        body: String,
+        error: DeError,
+    },
+}
+
+impl IntoResponse for AtomXmlRejection {
+    fn into_response(self) -> Response {
+        match self {
+            AtomXmlRejection::UnsupportedMediaType => {
+                StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response()
+            }
+            AtomXmlRejection::Body(rejection) => rejection.into_response(),
+            // Well formed xml that did not match the expected feed
+            AtomXmlRejection::Xml {
+                error: DeError::Custom(error),
+                ..
+            } => (StatusCode::UNPROCESSABLE_ENTITY, error).into_response(),
+            AtomXmlRejection::Xml { error, .. } => {
+                (StatusCode::BAD_REQUEST, error.to_string()).into_response()
+            }
+        }
+    }
+}
+
+impl<T: FromAtomXml, S: Send + Sync> FromRequest<S> for AtomXmlBody<T> {
+    type Rejection = AtomXmlRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_atom = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .and_then(|content_type| content_type.parse::<Mime>().ok())
+            .is_some_and(|content_type| content_type.essence_str() == "application/atom+xml");
+
+        if !is_atom {
+            return Err(AtomXmlRejection::UnsupportedMediaType);
+        }
+
+        let body = String::from_request(req, state)
+            .await
+            .map_err(AtomXmlRejection::Body)?;
+
+        match T::from_atom_xml(&body) {
+            Ok(value) => Ok(AtomXmlBody(value, body)),
+            Err(error) => Err(AtomXmlRejection::Xml { body, error }),
+        }
+    }
+}
@@ -2,9 +2,15 @@ use askama::Template;
 use axum::{extract::State, response::Html};
 use axum_extra::response::InternalServerError;
 use entity::video_queue_result;
+use entity_types::subscription_queue::SubscriptionAction;
 use sea_orm::{DatabaseConnection, DbErr, EntityTrait as _};
 
-use crate::database::{self, OAuth};
+use crate::database::{self, FailedFeedParses, OAuth, SubscriptionQueue};
+
+/// Failed subscription actions shown above the full queue
+const RECENT_ERRORS_LIMIT: u64 = 25;
+/// Feeds that could not be parsed, shown with a preview of their body
+const FAILED_FEED_PARSES_LIMIT: u64 = 20;
 
 #[derive(Template)]
 #[template(path = "dashboard.html")]
@@ -14,12 +20,17 @@ struct Dashboard {
         entity::subscription_queue::Model,
         Option<entity::subscription_queue_result::Model>,
     )>,
+    recent_errors: Vec<(
+        entity::subscription_queue::Model,
+        entity::subscription_queue_result::Model,
+    )>,
+    failed_feed_parses: Vec<entity::failed_feed_parses::Model>,
     video_queue: Vec<(
         entity::video_queue::Model,
         Option<video_queue_result::Model>,
     )>,
     known_channels: Vec<entity::known_channels::Model>,
-    css: String,
+    video_blacklist: Vec<entity::video_blacklist::Model>,
 }
 
 pub async fn dashboard(
@@ -35,6 +46,12 @@ pub async fn dashboard(
                 .all(&database)
                 .await
                 .map_err(InternalServerError)?,
+            recent_errors: SubscriptionQueue::get_failed_actions(&database, RECENT_ERRORS_LIMIT)
+                .await
+                .map_err(InternalServerError)?,
+            failed_feed_parses: FailedFeedParses::get_recent(&database, FAILED_FEED_PARSES_LIMIT)
+                .await
+                .map_err(InternalServerError)?,
             video_queue: entity::video_queue::Entity::find()
                 .find_also_related(entity::video_queue_result::Entity)
                 .all(&database)
@@ -44,9 +61,9 @@ pub async fn dashboard(
                 .all(&database)
                 .await
                 .map_err(InternalServerError)?,
-            css: tokio::fs::read_to_string("./static/styles.css")
+            video_blacklist: entity::video_blacklist::Entity::find()
+                .all(&database)
                 .await
-                .map_err(|e| DbErr::Custom(e.to_string()))
                 .map_err(InternalServerError)?,
         }
         .render()
@@ -0,0 +1,38 @@
+use axum::extract::State;
+use axum_extra::response::InternalServerError;
+use mail_send::mail_builder::MessageBuilder;
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, DbErr};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use crate::{actor::web::TailscaleUser, database::AuditLog};
+
+pub async fn test_email(
+    TailscaleUser(actor): TailscaleUser,
+    State((database, email_send_tx)): State<(
+        DatabaseConnection,
+        mpsc::Sender<MessageBuilder<'static>>,
+    )>,
+) -> Result<StatusCode, InternalServerError<DbErr>> {
+    let message = MessageBuilder::new()
+        .subject("Test email from like_and_subscribe")
+        .text_body(format!("This test email was requested by {actor}"));
+
+    match email_send_tx.try_send(message) {
+        Ok(()) => {}
+        Err(TrySendError::Full(_)) => {
+            tracing::warn!("email queue is full, unable to queue test email");
+            return Ok(StatusCode::SERVICE_UNAVAILABLE);
+        }
+        Err(TrySendError::Closed(_)) => {
+            tracing::error!("email sender has exited, unable to queue test email");
+            return Ok(StatusCode::SERVICE_UNAVAILABLE);
+        }
+    }
+
+    AuditLog::record_audit_event(&database, "email.test", &actor, None, None)
+        .await
+        .map_err(InternalServerError)?;
+
+    Ok(StatusCode::ACCEPTED)
+}
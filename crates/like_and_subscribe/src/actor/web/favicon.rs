@@ -0,0 +1,29 @@
+use axum::http::header;
+
+/// 1x1 fully transparent 32 bit icon
+const FAVICON: &[u8] = &[
+    // ICONDIR
+    0x00, 0x00, 0x01, 0x00, 0x01, 0x00, //
+    // ICONDIRENTRY, pointing at the bitmap below
+    0x01, 0x01, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x30, 0x00, 0x00, 0x00, 0x16, 0x00, 0x00,
+    0x00, //
+    // BITMAPINFOHEADER, with the height doubled to include the mask
+    0x28, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    // Transparent BGRA pixel
+    0x00, 0x00, 0x00, 0x00, //
+    // AND mask row, padded to 4 bytes
+    0x00, 0x00, 0x00, 0x00,
+];
+
+/// Served so that browsers visiting the dashboard do not fall through to the forbidden fallback
+pub async fn favicon() -> ([(header::HeaderName, &'static str); 2], &'static [u8]) {
+    (
+        [
+            (header::CONTENT_TYPE, "image/x-icon"),
+            (header::CACHE_CONTROL, "max-age=86400"),
+        ],
+        FAVICON,
+    )
+}
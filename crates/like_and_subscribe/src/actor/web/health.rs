@@ -0,0 +1,102 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use axum::{Json, extract::State};
+use reqwest::StatusCode;
+use serde::Serialize;
+
+use crate::task_metrics::{RunningTasks, TaskMonitors};
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Arc, time::Duration};
+
+    use axum::extract::State;
+    use reqwest::StatusCode;
+    use tokio::sync::Notify;
+
+    use crate::{
+        actor::web::health::health,
+        task_metrics::{RunningTasks, TaskMonitors, idle},
+    };
+
+    #[tokio::test]
+    async fn test_idle_actor_is_healthy() {
+        let monitors = TaskMonitors::default();
+        let running_tasks = RunningTasks::default();
+        let notify = Arc::new(Notify::new());
+
+        let running = running_tasks.insert("idle");
+        let task = tokio::spawn(monitors.instrument("idle", {
+            let notify = notify.clone();
+            async move {
+                let _running = running;
+                idle(notify.notified()).await
+            }
+        }));
+        tokio::task::yield_now().await;
+
+        // Long past the timeout, but still waiting for work
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let (status, _) = health(State((
+            monitors.clone(),
+            running_tasks.clone(),
+            Duration::from_millis(1),
+        )))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        notify.notify_one();
+        task.await.unwrap();
+
+        let (status, _) = health(State((monitors, running_tasks, Duration::from_millis(1)))).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Health {
+    actors: BTreeMap<&'static str, ActorHealth>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActorHealth {
+    healthy: bool,
+    /// `false` if the actor's task has exited
+    running: bool,
+    /// `true` if the actor is waiting for its next piece of work
+    idle: bool,
+    last_active_secs_ago: u64,
+}
+
+/// Reports an actor as unhealthy if it has exited, or is busy and has not been active within the
+/// configured timeout
+pub async fn health(
+    State((monitors, running_tasks, timeout)): State<(TaskMonitors, RunningTasks, Duration)>,
+) -> (StatusCode, Json<Health>) {
+    let actors = monitors
+        .metrics()
+        .into_iter()
+        .map(|metrics| {
+            let last_active = metrics.last_active.elapsed();
+            let running = running_tasks.contains(metrics.name);
+
+            (
+                metrics.name,
+                ActorHealth {
+                    healthy: running && (metrics.idle || last_active <= timeout),
+                    running,
+                    idle: metrics.idle,
+                    last_active_secs_ago: last_active.as_secs(),
+                },
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let status = if actors.values().all(|actor| actor.healthy) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(Health { actors }))
+}
@@ -1,41 +1,134 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    io,
+    net::SocketAddr,
+    sync::{Arc, atomic::AtomicBool},
+};
 
 use axum::{
-    extract::{Query, Request, State},
+    extract::{FromRequestParts, Query, Request, State},
+    http::{HeaderValue, Method, header, request::Parts},
     middleware::{self, Next},
     response::{Html, IntoResponse as _},
     routing::method_routing,
+    serve::ListenerExt as _,
 };
 use axum_extra::routing::RouterExt;
 use color_eyre::eyre::Context as _;
+use mail_send::mail_builder::MessageBuilder;
 use reqwest::StatusCode;
 use sea_orm::DatabaseConnection;
 use serde::Deserialize;
-use tokio::sync::Notify;
+use tokio::sync::{Notify, mpsc};
 use tokio_util::sync::CancellationToken;
 use tower::ServiceBuilder;
-use tower_http::{compression::CompressionLayer, trace::TraceLayer};
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{AllowOrigin, CorsLayer},
+    services::ServeDir,
+    trace::TraceLayer,
+};
+
+use crate::{
+    actor::web::tls::TlsListener,
+    config::Config,
+    database::AuditLog,
+    oauth::TokenManager,
+    task_metrics::{RunningTasks, TaskMonitors, idle},
+};
 
-use crate::oauth::TokenManager;
+const TAILSCALE_USER_LOGIN: &str = "Tailscale-User-Login";
+
+/// Login of the tailnet user making the request, as reported by `tailscale serve`
+pub struct TailscaleUser(pub String);
+
+impl<S: Send + Sync> FromRequestParts<S> for TailscaleUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .headers
+            .get(TAILSCALE_USER_LOGIN)
+            .and_then(|login| login.to_str().ok())
+            .map(|login| TailscaleUser(login.to_owned()))
+            .ok_or(StatusCode::UNAUTHORIZED)
+    }
+}
 
+mod api;
+mod atom;
 mod dashboard;
+mod email;
+mod favicon;
+mod health;
 mod pubsub;
+mod tls;
 
+#[tracing::instrument(skip_all)]
+#[expect(clippy::too_many_arguments)]
 pub async fn web_server(
     shutdown: CancellationToken,
     database: DatabaseConnection,
     video_queue_notify: Arc<Notify>,
+    subscriptions_queue_notify: Arc<Notify>,
     token_manager: TokenManager,
+    task_monitors: TaskMonitors,
+    running_tasks: RunningTasks,
+    email_send_tx: mpsc::Sender<MessageBuilder<'static>>,
+    config: Arc<Config>,
+    client: reqwest::Client,
 ) -> color_eyre::Result<()> {
     let tailscale_auth = middleware::from_fn(|req: Request, next: Next| async {
         // TODO: Verify that these are filtered by tailscale funnel
-        if req.headers().contains_key("Tailscale-User-Login") {
+        if req.headers().contains_key(TAILSCALE_USER_LOGIN) {
             next.run(req).await
         } else {
             axum::http::StatusCode::UNAUTHORIZED.into_response()
         }
     });
 
+    // Only applied to the admin routes, the pubsub hub does not need them
+    let security_headers = middleware::from_fn(|req: Request, next: Next| async {
+        let mut response = next.run(req).await;
+        let headers = response.headers_mut();
+
+        headers.insert(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        );
+        headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+        // Disable the legacy filter, the content security policy replaces it
+        headers.insert(header::X_XSS_PROTECTION, HeaderValue::from_static("0"));
+        headers.insert(
+            header::REFERRER_POLICY,
+            HeaderValue::from_static("strict-origin-when-cross-origin"),
+        );
+        // Channel profile pictures are hotlinked from youtube
+        headers.insert(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static("default-src 'self'; img-src 'self' https:"),
+        );
+
+        response
+    });
+
+    // Preflight requests are answered before authentication, as browsers do not send credentials with them
+    let cors = CorsLayer::new()
+        .allow_origin(AllowOrigin::exact(
+            HeaderValue::from_str(&format!("https://{}", config.hostname))
+                .wrap_err("HOSTNAME is not a valid origin")?,
+        ))
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
+        .allow_headers([header::CONTENT_TYPE])
+        .allow_credentials(true);
+
+    let maintenance_mode = Arc::new(AtomicBool::new(false));
+
     let admin_router = axum::Router::new()
         .route_with_tsr("/auth", {
             #[derive(Deserialize)]
@@ -43,29 +136,58 @@ pub async fn web_server(
                 code: oauth2::AuthorizationCode,
             }
             method_routing::get(
-                async |Query(params): Query<Params>, State(token_manager): State<TokenManager>| {
+                async |Query(params): Query<Params>, TailscaleUser(actor): TailscaleUser, State((token_manager, database)): State<(TokenManager, DatabaseConnection)>| {
                     match token_manager.load_new_token(params.code).await {
-                        Ok(()) => Html("<!DOCTYPE html><html><head><script>window.close()</script></head><body>Authenticated</body></html>").into_response(),
+                        Ok(()) => {
+                            if let Err(error) = AuditLog::record_audit_event(&database, "oauth.authenticate", &actor, None, None).await {
+                                tracing::error!(%error, "failed to record audit event");
+                            }
+
+                            Html("<!DOCTYPE html><html><head><script src=\"/admin/static/close_window.js\"></script></head><body>Authenticated</body></html>").into_response()
+                        },
                         Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{error:#?}")).into_response(),
                     }
                 },
             )
-            .with_state(token_manager)
+            .with_state((token_manager.clone(), database.clone()))
         })
+        .nest_service("/static", ServeDir::new("./static"))
         .route_service_with_tsr("/dashboard", method_routing::get(dashboard::dashboard).with_state(database.clone()))
-        .layer(tailscale_auth);
+        .route_with_tsr("/test-email", method_routing::post(email::test_email).with_state((database.clone(), email_send_tx)))
+        .nest("/api", api::router(database.clone(), subscriptions_queue_notify, video_queue_notify.clone(), task_monitors.clone(), config.clone(), maintenance_mode.clone(), client, token_manager))
+        .layer(tailscale_auth)
+        .layer(cors)
+        .layer(security_headers);
 
     let pubsub_router = axum::Router::new().route_with_tsr(
         "/pubsub",
         method_routing::get(pubsub::pubsub_subscription_validation)
             .with_state(database.clone())
             .post(pubsub::pubsub_new_upload)
-            .with_state((database, video_queue_notify)),
+            .with_state(pubsub::NewUploadState {
+                database,
+                notification: video_queue_notify,
+                maintenance_mode,
+                allowed_ip_ranges: config.pubsub_allowed_ip_ranges.clone().map(Arc::from),
+                rate_limiter: pubsub::SenderRateLimiter::default(),
+                config: config.clone(),
+            }),
+    );
+
+    let health_router = axum::Router::new().route_with_tsr(
+        "/health",
+        method_routing::get(health::health).with_state((
+            task_monitors,
+            running_tasks,
+            config.actor_health_timeout(),
+        )),
     );
 
     let router = axum::Router::new()
         .nest("/admin", admin_router)
         .merge(pubsub_router)
+        .merge(health_router)
+        .route("/favicon.ico", method_routing::get(favicon::favicon))
         .fallback(method_routing::any(|| async {
             axum::http::StatusCode::FORBIDDEN // TODO: IPBAN or other honeypot
         }))
@@ -75,13 +197,56 @@ pub async fn web_server(
                 .layer(CompressionLayer::new()),
         );
 
-    axum::serve(
-        tokio::net::TcpListener::bind("127.0.0.1:8080")
+    let shutdown = async move { shutdown.cancelled().await };
+
+    // The server is only polled when accepting connections, so count it as idle while waiting. It
+    // answers the health check itself, so it cannot be stuck while the check succeeds.
+
+    if let Some(path) = &config.unix_socket_path {
+        // Remove the socket left behind by the previous run
+        match std::fs::remove_file(path) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(error).wrap_err_with(|| format!("unable to remove {}", path.display()));
+            }
+        }
+
+        let listener = tokio::net::UnixListener::bind(path)
+            .wrap_err_with(|| format!("unable to bind to {}", path.display()))?;
+        tracing::info!(path = %path.display(), "listening on unix socket");
+
+        // Peer addresses are meaningless for unix sockets, so no ConnectInfo is provided
+        return idle(
+            axum::serve(listener, router.into_make_service()).with_graceful_shutdown(shutdown),
+        )
+        .await
+        .wrap_err("failed to run axum server");
+    }
+
+    let tls_acceptor = config.tls.as_ref().map(tls::load_acceptor).transpose()?;
+
+    let listener = tokio::net::TcpListener::bind(config.bind_address)
+        .await
+        .wrap_err_with(|| format!("unable to bind to {}", config.bind_address))?;
+    tracing::info!(address = %config.bind_address, "listening on tcp");
+    let make_service = router.into_make_service_with_connect_info::<SocketAddr>();
+
+    match tls_acceptor {
+        Some(acceptor) => {
+            tracing::info!("terminating tls in the web server");
+
+            idle(
+                axum::serve(
+                    // Tapping provides the ConnectInfo implementation for the listener's address
+                    TlsListener::new(listener, acceptor).tap_io(|_| {}),
+                    make_service,
+                )
+                .with_graceful_shutdown(shutdown),
+            )
             .await
-            .wrap_err("unable to bind to port 8080")?,
-        router.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .with_graceful_shutdown(async move { shutdown.cancelled().await })
-    .await
+        }
+        None => idle(axum::serve(listener, make_service).with_graceful_shutdown(shutdown)).await,
+    }
     .wrap_err("failed to run axum server")
 }
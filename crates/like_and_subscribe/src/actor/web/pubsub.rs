@@ -1,19 +1,122 @@
-use std::{str::FromStr as _, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
-use axum::extract::{Query, State, rejection::QueryRejection};
-use axum_extra::{TypedHeader, headers::ContentType};
-use jiff::Zoned;
-use mime::Mime;
-use quick_xml::DeError;
+use axum::{
+    Extension,
+    extract::{ConnectInfo, Query, State, rejection::QueryRejection},
+    http::{HeaderMap, HeaderValue, header},
+    response::{IntoResponse as _, Response},
+};
+use entity::active_subscriptions;
+use ipnet::IpNet;
+use jiff::{Timestamp, Zoned};
 use reqwest::StatusCode;
+use ring::hmac;
 use sea_orm::DatabaseConnection;
 use serde::Deserialize;
 use tokio::sync::Notify;
 use tracing::warn;
 
-use crate::database::{ActiveSubscriptions, VideoQueue};
+use crate::actor::web::atom::{AtomXmlBody, AtomXmlRejection};
+use crate::config::Config;
+use crate::database::{
+    ActiveSubscriptions, FailedFeedParses, KnownChannels, MaintenanceWindows, SubscriptionQueue,
+    VideoBlacklist, VideoQueue,
+};
 use crate::feed::Feed;
 
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use axum::http::{HeaderMap, HeaderValue};
+
+    use crate::actor::web::pubsub::{
+        NEW_UPLOAD_RATE_LIMIT, SenderRateLimiter, resolve_sender, verify_signature,
+    };
+
+    #[test]
+    fn rate_limit_per_sender() {
+        let limiter = SenderRateLimiter::default();
+        let sender = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        let other_sender = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+
+        for _ in 0..NEW_UPLOAD_RATE_LIMIT {
+            assert!(limiter.check(sender));
+        }
+
+        assert!(!limiter.check(sender));
+        assert!(limiter.check(other_sender));
+    }
+
+    #[test]
+    fn rate_limit_per_forwarded_sender() {
+        let limiter = SenderRateLimiter::default();
+        let proxy = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let forwarded_for = |sender: &'static str| {
+            HeaderMap::from_iter([(
+                "x-forwarded-for".parse().unwrap(),
+                HeaderValue::from_static(sender),
+            )])
+        };
+
+        let sender = resolve_sender(proxy, &forwarded_for("192.0.2.1")).unwrap();
+        let other_sender = resolve_sender(proxy, &forwarded_for("203.0.113.7, 192.0.2.2")).unwrap();
+        assert_eq!(sender, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+        assert_eq!(other_sender, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2)));
+
+        for _ in 0..NEW_UPLOAD_RATE_LIMIT {
+            assert!(limiter.check(sender));
+        }
+
+        assert!(!limiter.check(sender));
+        assert!(limiter.check(other_sender));
+    }
+
+    #[test]
+    fn forwarded_for_only_trusted_from_proxy() {
+        let headers = HeaderMap::from_iter([(
+            "x-forwarded-for".parse().unwrap(),
+            HeaderValue::from_static("192.0.2.1"),
+        )]);
+        let peer = IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1));
+
+        assert_eq!(resolve_sender(Some(peer), &headers), Some(peer));
+        assert_eq!(
+            resolve_sender(None, &headers),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)))
+        );
+        assert_eq!(
+            resolve_sender(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)), &HeaderMap::new()),
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+    }
+
+    #[test]
+    fn signature_verification() {
+        let body = "<feed></feed>";
+        // HMAC-SHA1 of the body with the key "secret"
+        let signature = HeaderValue::from_static("sha1=87695bea5c072947a930fa324488bd09f7483880");
+
+        assert!(verify_signature("secret", Some(&signature), body));
+        assert!(!verify_signature("other secret", Some(&signature), body));
+        assert!(!verify_signature("secret", Some(&signature), "<feed/>"));
+        assert!(!verify_signature("secret", None, body));
+        assert!(!verify_signature(
+            "secret",
+            Some(&HeaderValue::from_static("sha1=not hex")),
+            body
+        ));
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "hub.mode")]
 pub enum HubChallenge {
@@ -81,9 +184,21 @@ pub async fn pubsub_subscription_validation(
                 )
                 .timestamp();
 
-            let database_result =
-                ActiveSubscriptions::add_subscription(&database, channel_id.to_owned(), expiration)
-                    .await;
+            let secret = match SubscriptionQueue::confirm_secret(&database, channel_id).await {
+                Ok(secret) => secret,
+                Err(error) => {
+                    tracing::error!(%error, "failed to get subscription secret");
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
+
+            let database_result = ActiveSubscriptions::add_subscription(
+                &database,
+                channel_id.to_owned(),
+                expiration,
+                secret,
+            )
+            .await;
 
             match database_result {
                 Ok(_) => Ok(query.challenge),
@@ -100,44 +215,255 @@ pub async fn pubsub_subscription_validation(
     }
 }
 
+#[derive(Clone)]
+pub struct NewUploadState {
+    pub database: DatabaseConnection,
+    pub notification: Arc<Notify>,
+    /// New uploads are deferred while enabled
+    pub maintenance_mode: Arc<AtomicBool>,
+    /// New uploads from outside of these ranges are rejected, if set
+    pub allowed_ip_ranges: Option<Arc<[IpNet]>>,
+    pub rate_limiter: SenderRateLimiter,
+    pub config: Arc<Config>,
+}
+
+/// Value of the `Retry-After` header sent while in maintenance mode
+const MAINTENANCE_RETRY_AFTER_SECS: &str = "300";
+
+/// Maximum number of new uploads accepted from a single ip per [`NEW_UPLOAD_RATE_LIMIT_WINDOW`]
+const NEW_UPLOAD_RATE_LIMIT: u32 = 60;
+const NEW_UPLOAD_RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+/// Value of the `Retry-After` header sent to rate limited senders
+const RATE_LIMIT_RETRY_AFTER_SECS: &str = "1";
+
+/// Fixed window rate limiter for new uploads, keyed by the sender's ip from [`resolve_sender`]
+#[derive(Clone, Default)]
+pub struct SenderRateLimiter {
+    windows: Arc<Mutex<HashMap<IpAddr, (Instant, u32)>>>,
+}
+
+impl SenderRateLimiter {
+    /// Returns `false` if the sender has used up its requests for the current window
+    fn check(&self, sender: IpAddr) -> bool {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().expect("mutex should not be poisoned");
+
+        // Forget senders whose window has ended, so the map does not grow forever
+        windows.retain(|_, (start, _)| now.duration_since(*start) < NEW_UPLOAD_RATE_LIMIT_WINDOW);
+
+        let (_, count) = windows.entry(sender).or_insert((now, 0));
+        *count = count.saturating_add(1);
+
+        *count <= NEW_UPLOAD_RATE_LIMIT
+    }
+}
+
+/// Ip of the sender of a request, using the `X-Forwarded-For` header added by tailscale or a
+/// reverse proxy when the peer is on the same host
+///
+/// Only the last address in the header is used, since it is the one added by the proxy itself
+fn resolve_sender(peer: Option<IpAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+    // Missing peers are served over a unix socket, which is only reachable from the same host
+    if !peer.is_none_or(|peer| peer.is_loopback()) {
+        return peer;
+    }
+
+    let forwarded = headers
+        .get("X-Forwarded-For")
+        .and_then(|forwarded_for| forwarded_for.to_str().ok())
+        .and_then(|forwarded_for| forwarded_for.rsplit(',').next())
+        .and_then(|sender| sender.trim().parse().ok());
+
+    forwarded.or(peer)
+}
+
 pub async fn pubsub_new_upload(
-    // connect: ConnectInfo<SocketAddr>,
+    // Missing when served over a unix socket
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
     // TypedHeader(user_agent): TypedHeader<UserAgent>,
-    TypedHeader(content_type): TypedHeader<ContentType>,
-    State((database, notification)): State<(DatabaseConnection, Arc<Notify>)>,
-    body: String,
-) -> StatusCode {
-    if Mime::from(content_type)
-        != Mime::from_str("application/atom+xml").expect("mime should be valid")
+    State(NewUploadState {
+        database,
+        notification,
+        maintenance_mode,
+        allowed_ip_ranges,
+        rate_limiter,
+        config,
+    }): State<NewUploadState>,
+    headers: HeaderMap,
+    // Rejected after the sender checks, so that failed parses can be recorded
+    body: Result<AtomXmlBody<Feed>, AtomXmlRejection>,
+) -> Response {
+    let sender = resolve_sender(
+        connect_info.map(|Extension(ConnectInfo(peer))| peer.ip()),
+        &headers,
+    );
+
+    // Senders without a known ip can not be checked, so are rejected too
+    if let Some(allowed_ip_ranges) = allowed_ip_ranges
+        && !sender.is_some_and(|sender| {
+            allowed_ip_ranges
+                .iter()
+                .any(|range| range.contains(&sender))
+        })
     {
-        return StatusCode::UNSUPPORTED_MEDIA_TYPE;
+        warn!(?sender, "rejecting new upload from disallowed ip");
+        return StatusCode::FORBIDDEN.into_response();
     }
 
-    // TODO: verify remote IP, user agent and others??
-    // tokio::net::lookup_host("pubsubhubbub.appspot.com").await
+    // Requests from the same host without a forwarded sender would all share one window
+    if let Some(sender) = sender
+        && !sender.is_loopback()
+        && !rate_limiter.check(sender)
+    {
+        warn!(%sender, "rate limiting new uploads");
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, RATE_LIMIT_RETRY_AFTER_SECS)],
+        )
+            .into_response();
+    }
 
-    // TODO: store bad XML feed items in database instead of logging or something for debugging (due to "missing field `@xmlns:yt`")
-    let feed = match quick_xml::de::from_str::<Feed>(&body) {
-        Ok(feed) => feed,
-        Err(DeError::Custom(error)) => {
-            warn!(%error, %body, "unable to process valid xml feed item");
-            return StatusCode::UNPROCESSABLE_ENTITY;
+    if maintenance_mode.load(Ordering::Relaxed) {
+        // Have the hub retry the notification once maintenance is over
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, MAINTENANCE_RETRY_AFTER_SECS)],
+        )
+            .into_response();
+    }
+
+    match MaintenanceWindows::get_active_end(&database, Timestamp::now()).await {
+        Ok(None) => {}
+        Ok(Some(ends_at)) => {
+            // Have the hub retry the notification once the window has ended
+            let retry_after = Timestamp::now()
+                .duration_until(ends_at)
+                .as_secs()
+                .max(1)
+                .to_string();
+
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, retry_after)],
+            )
+                .into_response();
         }
         Err(error) => {
-            warn!(%error, %body, "unable to parse incoming feed item");
-            return StatusCode::BAD_REQUEST;
+            tracing::error!(%error, "failed to check for maintenance windows");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    // TODO: verify user agent and others??
+    // tokio::net::lookup_host("pubsubhubbub.appspot.com").await
+
+    let (feed, body) = match body {
+        Ok(AtomXmlBody(feed, body)) => (feed, body),
+        Err(rejection) => {
+            if let AtomXmlRejection::Xml { body, error } = &rejection {
+                warn!(%error, %body, "unable to parse incoming feed item");
+                record_failed_feed_parse(&database, body, &error.to_string()).await;
+            }
+
+            return rejection.into_response();
         }
     };
 
-    let database_result = VideoQueue::new_video(&database, feed.entry).await;
+    // Subscriptions made before secrets were sent to the hub have no signature to check
+    match ActiveSubscriptions::get_subscription(&database, feed.channel_id()).await {
+        Ok(Some(active_subscriptions::Model {
+            secret: Some(secret),
+            ..
+        })) => {
+            if !verify_signature(&secret, headers.get("X-Hub-Signature"), &body) {
+                // The hub expects a success response even if the signature does not match
+                warn!(
+                    channel_id = feed.channel_id(),
+                    "ignoring new upload with invalid signature"
+                );
+                return StatusCode::OK.into_response();
+            }
+        }
+        Ok(_) => {}
+        Err(error) => {
+            tracing::error!(%error, "failed to get subscription secret");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    match KnownChannels::is_notifications_paused(&database, feed.channel_id()).await {
+        Ok(false) => {}
+        Ok(true) => {
+            tracing::debug!(
+                channel_id = feed.channel_id(),
+                "ignoring video from paused channel"
+            );
+            return StatusCode::OK.into_response();
+        }
+        Err(error) => {
+            tracing::error!(%error, "failed to check if channel is paused");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
 
-    if let Err(error) = database_result {
-        tracing::error!(%error, "failed to insert video into queue");
-        return StatusCode::INTERNAL_SERVER_ERROR;
+    match VideoBlacklist::is_blacklisted(&database, feed.video_id()).await {
+        Ok(false) => {}
+        Ok(true) => {
+            tracing::debug!(video_id = feed.video_id(), "ignoring blacklisted video");
+            return StatusCode::OK.into_response();
+        }
+        Err(error) => {
+            tracing::error!(%error, "failed to check if video is blacklisted");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    let video_id = feed.video_id().to_owned();
+    let target_playlist_id = config.playlist_id_for(feed.channel_id());
+
+    match VideoQueue::new_video(
+        &database,
+        feed.into_entry(),
+        sender.map(|sender| sender.to_string()),
+        target_playlist_id,
+        config.video_age_minutes_threshold,
+    )
+    .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::debug!(video_id, "ignoring already queued video");
+            return StatusCode::OK.into_response();
+        }
+        Err(error) => {
+            tracing::error!(%error, "failed to insert video into queue");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
     }
 
     tracing::trace!("notifying new video queue");
-    notification.notify_waiters();
+    notification.notify_one();
 
-    StatusCode::ACCEPTED
+    StatusCode::ACCEPTED.into_response()
+}
+
+/// Check the `sha1=<hex>` signature that the hub sends with the HMAC of the body
+fn verify_signature(secret: &str, signature: Option<&HeaderValue>, body: &str) -> bool {
+    let Some(signature) = signature
+        .and_then(|signature| signature.to_str().ok())
+        .and_then(|signature| signature.strip_prefix("sha1="))
+        .and_then(|signature| hex::decode(signature).ok())
+    else {
+        return false;
+    };
+
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret.as_bytes());
+
+    hmac::verify(&key, body.as_bytes(), &signature).is_ok()
+}
+
+async fn record_failed_feed_parse(database: &DatabaseConnection, body: &str, error: &str) {
+    if let Err(error) = FailedFeedParses::record(database, body, error).await {
+        tracing::error!(%error, "failed to record failed feed parse");
+    }
 }
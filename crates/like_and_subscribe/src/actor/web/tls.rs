@@ -0,0 +1,110 @@
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::serve::Listener;
+use color_eyre::eyre::Context as _;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinSet,
+};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{
+        ServerConfig,
+        pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject as _},
+    },
+    server::TlsStream,
+};
+
+use crate::config::TlsConfig;
+
+/// Clients that take longer than this to finish the handshake are dropped, so that slow
+/// handshakes do not pile up
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn load_acceptor(config: &TlsConfig) -> color_eyre::Result<TlsAcceptor> {
+    let certificates = CertificateDer::pem_file_iter(&config.cert_path)
+        .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+        .wrap_err_with(|| {
+            format!(
+                "unable to read certificate chain from {}",
+                config.cert_path.display()
+            )
+        })?;
+    let key = PrivateKeyDer::from_pem_file(&config.key_path).wrap_err_with(|| {
+        format!(
+            "unable to read private key from {}",
+            config.key_path.display()
+        )
+    })?;
+
+    let mut server_config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+        .wrap_err("invalid certificate or private key")?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// Performs the tls handshake on each accepted tcp connection
+///
+/// Handshakes run concurrently, so that a slow client cannot stall other connections from
+/// being accepted
+pub struct TlsListener {
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    handshakes: JoinSet<Option<(TlsStream<TcpStream>, SocketAddr)>>,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, acceptor: TlsAcceptor) -> Self {
+        Self {
+            listener,
+            acceptor,
+            handshakes: JoinSet::new(),
+        }
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            tokio::select! {
+                (stream, address) = Listener::accept(&mut self.listener) => {
+                    self.handshakes.spawn(handshake(self.acceptor.clone(), stream, address));
+                }
+                // Disabled while there are no handshakes in progress
+                Some(handshake) = self.handshakes.join_next() => match handshake {
+                    Ok(Some(connection)) => return connection,
+                    Ok(None) => {}
+                    Err(error) => tracing::warn!(%error, "tls handshake task failed"),
+                },
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.listener.local_addr()
+    }
+}
+
+async fn handshake(
+    acceptor: TlsAcceptor,
+    stream: TcpStream,
+    address: SocketAddr,
+) -> Option<(TlsStream<TcpStream>, SocketAddr)> {
+    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+        Ok(Ok(stream)) => Some((stream, address)),
+        Ok(Err(error)) => {
+            tracing::debug!(%error, %address, "tls handshake failed");
+            None
+        }
+        Err(_) => {
+            tracing::debug!(%address, "tls handshake timed out");
+            None
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use askama::Template;
+use entity::known_channels;
+use mail_send::mail_builder::MessageBuilder;
+use tokio::{sync::mpsc, time::Instant};
+
+#[cfg(test)]
+mod test {
+    use crate::alert::AlertKind;
+
+    #[test]
+    fn test_reauth_email_is_multipart() {
+        let email = AlertKind::Reauthenticate {
+            url: "https://example.com/auth?a=b&c=d".to_string(),
+        }
+        .into_email()
+        .unwrap()
+        .write_to_string()
+        .unwrap();
+
+        assert!(email.contains("Content-Type: multipart/alternative"));
+
+        let plain_text = email
+            .find("Content-Type: text/plain")
+            .expect("email should have a plain text part");
+        let html = email
+            .find("Content-Type: text/html")
+            .expect("email should have an html part");
+
+        // Clients prefer the last alternative they support
+        assert!(plain_text < html);
+        assert!(email.contains("Re-authenticate: https://example.com/auth?a=b&c=d"));
+    }
+}
+
+/// Quota exceeded alerts are sent at most once in this interval, since every
+/// failed api call would otherwise send another email
+const QUOTA_EXCEEDED_ALERT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// The queue stays nearly full until it catches up, so only remind every so often
+const QUEUE_NEARLY_FULL_ALERT_INTERVAL: Duration = Duration::from_secs(60 * 60 * 6);
+/// Every rejected action would otherwise send another email while the queue stays full
+const QUEUE_FULL_ALERT_INTERVAL: Duration = Duration::from_secs(60 * 60 * 6);
+
+pub enum AlertKind {
+    Reauthenticate {
+        url: String,
+    },
+    SubscriptionFailed {
+        /// Channel id and the last error for each subscription that became a dead letter
+        failures: Vec<(String, String)>,
+    },
+    QuotaExceeded,
+    InactiveChannels {
+        channels: Vec<known_channels::Model>,
+        /// Days without an upload before a channel is inactive
+        days: i64,
+    },
+    SubscriptionQueueNearlyFull {
+        pending: u64,
+        max_rows: u64,
+    },
+    SubscriptionQueueFull {
+        max_rows: u64,
+    },
+}
+
+#[derive(Template)]
+#[template(path = "email_reauth.html")]
+struct ReauthEmail<'a> {
+    url: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "email_subscription_failed.html")]
+struct SubscriptionFailedEmail<'a> {
+    failures: &'a [(String, String)],
+}
+
+#[derive(Template)]
+#[template(path = "email_quota_exceeded.html")]
+struct QuotaExceededEmail;
+
+#[derive(Template)]
+#[template(path = "email_inactive_channels.html")]
+struct InactiveChannelsEmail<'a> {
+    channels: &'a [known_channels::Model],
+    days: i64,
+}
+
+#[derive(Template)]
+#[template(path = "email_queue_nearly_full.html")]
+struct QueueNearlyFullEmail {
+    pending: u64,
+    max_rows: u64,
+}
+
+#[derive(Template)]
+#[template(path = "email_queue_full.html")]
+struct QueueFullEmail {
+    max_rows: u64,
+}
+
+impl AlertKind {
+    fn into_email(self) -> Result<MessageBuilder<'static>, askama::Error> {
+        Ok(match self {
+            AlertKind::Reauthenticate { url } => MessageBuilder::new()
+                .subject("Re-authenticate with google to continue")
+                .text_body(format!("Re-authenticate: {url}"))
+                .html_body(ReauthEmail { url: &url }.render()?),
+            AlertKind::SubscriptionFailed { failures } => MessageBuilder::new()
+                .subject(format!("Failed to update {} subscriptions", failures.len()))
+                .text_body(
+                    failures
+                        .iter()
+                        .map(|(channel_id, error)| format!("{channel_id}: {error}\n"))
+                        .collect::<String>(),
+                )
+                .html_body(
+                    SubscriptionFailedEmail {
+                        failures: &failures,
+                    }
+                    .render()?,
+                ),
+            AlertKind::QuotaExceeded => MessageBuilder::new()
+                .subject("YouTube API quota exceeded")
+                .text_body("The daily YouTube Data API quota has been used up")
+                .html_body(QuotaExceededEmail.render()?),
+            AlertKind::InactiveChannels { channels, days } => MessageBuilder::new()
+                .subject(format!(
+                    "{} channels have not posted in {days} days",
+                    channels.len()
+                ))
+                .text_body(
+                    channels
+                        .iter()
+                        .map(|channel| {
+                            format!(
+                                "{} (https://www.youtube.com/channel/{})\n",
+                                channel.channel_name, channel.channel_id
+                            )
+                        })
+                        .collect::<String>(),
+                )
+                .html_body(
+                    InactiveChannelsEmail {
+                        channels: &channels,
+                        days,
+                    }
+                    .render()?,
+                ),
+            AlertKind::SubscriptionQueueNearlyFull { pending, max_rows } => MessageBuilder::new()
+                .subject("Subscription queue nearly full")
+                .text_body(format!(
+                    "{pending} of {max_rows} subscription actions are waiting to be processed"
+                ))
+                .html_body(QueueNearlyFullEmail { pending, max_rows }.render()?),
+            AlertKind::SubscriptionQueueFull { max_rows } => MessageBuilder::new()
+                .subject("Subscription queue full")
+                .text_body(format!(
+                    "{max_rows} subscription actions are waiting to be processed, new actions are being rejected"
+                ))
+                .html_body(QueueFullEmail { max_rows }.render()?),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct AlertSender {
+    mail_send: mpsc::Sender<MessageBuilder<'static>>,
+    last_quota_exceeded_alert: Arc<Mutex<Option<Instant>>>,
+    last_queue_nearly_full_alert: Arc<Mutex<Option<Instant>>>,
+    last_queue_full_alert: Arc<Mutex<Option<Instant>>>,
+}
+
+impl AlertSender {
+    pub fn new(mail_send: mpsc::Sender<MessageBuilder<'static>>) -> Self {
+        Self {
+            mail_send,
+            last_quota_exceeded_alert: Arc::default(),
+            last_queue_nearly_full_alert: Arc::default(),
+            last_queue_full_alert: Arc::default(),
+        }
+    }
+
+    pub async fn send_alert(&self, kind: AlertKind) {
+        let throttle = match kind {
+            AlertKind::QuotaExceeded => Some((
+                &self.last_quota_exceeded_alert,
+                QUOTA_EXCEEDED_ALERT_INTERVAL,
+            )),
+            AlertKind::SubscriptionQueueNearlyFull { .. } => Some((
+                &self.last_queue_nearly_full_alert,
+                QUEUE_NEARLY_FULL_ALERT_INTERVAL,
+            )),
+            AlertKind::SubscriptionQueueFull { .. } => {
+                Some((&self.last_queue_full_alert, QUEUE_FULL_ALERT_INTERVAL))
+            }
+            _ => None,
+        };
+
+        if let Some((last_alert, interval)) = throttle {
+            let mut last_alert = last_alert.lock().expect("mutex should not be poisoned");
+
+            if last_alert.is_some_and(|last_alert| last_alert.elapsed() < interval) {
+                tracing::debug!("alert already sent recently");
+                return;
+            }
+
+            *last_alert = Some(Instant::now());
+        }
+
+        let email = match kind.into_email() {
+            Ok(email) => email,
+            Err(error) => {
+                tracing::error!(%error, "failed to render alert email");
+                return;
+            }
+        };
+
+        tracing::info!("Queuing email");
+        if self.mail_send.send(email).await.is_err() {
+            tracing::error!("email sender has exited, unable to queue alert email");
+        }
+    }
+}
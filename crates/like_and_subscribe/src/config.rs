@@ -0,0 +1,306 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    env::VarError,
+    error::Error,
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use color_eyre::eyre::{Context as _, bail};
+use ipnet::IpNet;
+use jiff::SignedDuration;
+use serde::{Serialize, Serializer};
+
+use crate::actor::pubsubhubbub::queue::Verify;
+
+#[derive(Debug, Serialize)]
+pub struct Config {
+    /// Public hostname that the pubsubhubbub hub and oauth redirects are sent to
+    pub hostname: String,
+    /// Playlists that new videos are added to, keyed by channel id with a `*` fallback
+    pub youtube_playlist_routing: HashMap<String, String>,
+    #[serde(skip)]
+    pub database_url: String,
+
+    pub google_client_id: String,
+    #[serde(serialize_with = "redact")]
+    pub google_client_secret: String,
+
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: String,
+    #[serde(serialize_with = "redact")]
+    pub smtp_password: String,
+
+    /// How long to wait for tasks to exit after a shutdown is requested
+    pub shutdown_timeout_secs: u64,
+    /// Feed items updated more than this many minutes after being published are
+    /// edits to existing videos rather than new uploads
+    ///
+    /// Can be overridden per channel with [`entity::known_channels::Model::video_age_threshold_minutes`]
+    pub video_age_minutes_threshold: f64,
+    /// Outgoing http requests are limited to this many per period
+    pub http_rate_limit_count: u64,
+    pub http_rate_limit_period_secs: u64,
+    /// With asynchronous verification the hub responds before the challenge is sent to the callback
+    pub pubsub_verify_mode: Verify,
+    /// New upload notifications from outside of these ranges are rejected, if set. Checked
+    /// against the forwarded sender for requests proxied from the same host
+    pub pubsub_allowed_ip_ranges: Option<Vec<IpNet>>,
+    /// Lower values reduce how often the hub rate limits subscription requests
+    pub max_concurrent_subscriptions: usize,
+    /// Lower values reduce YouTube API quota consumption
+    pub max_concurrent_videos: usize,
+    /// Busy actors that have not been active for this long are reported as unhealthy, while
+    /// actors waiting for their next piece of work are always healthy
+    pub actor_health_timeout_secs: u64,
+    /// The smtp server closes idle connections, so a failed send is retried on a
+    /// fresh connection this many times before the email is discarded
+    pub smtp_max_retries: u32,
+    /// Failed pubsubhubbub subscription actions are attempted at most this many times, after
+    /// which they are left as dead letters
+    pub subscription_max_retries: u32,
+    /// Subscription actions are rejected while this many are waiting to be processed
+    pub subscription_queue_max_rows: u64,
+    /// Webhook for event notifications, only checked with the test endpoint so far. Redacted
+    /// since it may contain a token
+    #[serde(serialize_with = "redact_optional")]
+    pub webhook_url: Option<String>,
+    /// Channels that have not uploaded for this many days are reported as inactive and can be
+    /// unsubscribed from in bulk
+    pub inactive_channel_days: u32,
+    /// Processed videos are deleted from the queue once they are this many days old
+    pub video_queue_retention_days: u32,
+    /// Remove videos detected as shorts from the playlist when it is checked each day
+    pub playlist_remove_shorts: bool,
+    /// Periodically log http connection pool stats, always enabled in debug builds
+    pub reqwest_pool_stats: bool,
+    /// Terminate tls in the web server instead of relying on tailscale or a reverse proxy
+    pub tls: Option<TlsConfig>,
+    /// Serve on a unix socket instead of `bind_address`, for reverse proxies on the same host
+    pub unix_socket_path: Option<PathBuf>,
+    /// Address the web server listens on, which has to be reachable from outside of the host
+    /// when terminating tls
+    pub bind_address: SocketAddr,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TlsConfig {
+    /// Pem encoded certificate chain
+    pub cert_path: PathBuf,
+    /// Pem encoded private key
+    pub key_path: PathBuf,
+}
+
+fn redact<S: Serializer>(_: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str("***")
+}
+
+fn redact_optional<S: Serializer>(
+    value: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(_) => serializer.serialize_str("***"),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Key of the playlist used for channels without their own routing
+const PLAYLIST_ROUTING_FALLBACK: &str = "*";
+
+/// Read from a json object in `YOUTUBE_PLAYLIST_ROUTING`, falling back to sending every
+/// channel to the playlist in `YOUTUBE_PLAYLIST_ID`
+fn playlist_routing() -> color_eyre::Result<HashMap<String, String>> {
+    let routing = match std::env::var("YOUTUBE_PLAYLIST_ROUTING") {
+        Ok(routing) => serde_json::from_str::<HashMap<String, String>>(&routing)
+            .wrap_err("unable to parse YOUTUBE_PLAYLIST_ROUTING env var")?,
+        Err(VarError::NotPresent) => HashMap::from([(
+            PLAYLIST_ROUTING_FALLBACK.to_owned(),
+            var("YOUTUBE_PLAYLIST_ID")?,
+        )]),
+        Err(error) => {
+            return Err(error).wrap_err("unable to read YOUTUBE_PLAYLIST_ROUTING env var");
+        }
+    };
+
+    if !routing.contains_key(PLAYLIST_ROUTING_FALLBACK) {
+        bail!(
+            "YOUTUBE_PLAYLIST_ROUTING must have a {PLAYLIST_ROUTING_FALLBACK:?} fallback playlist"
+        );
+    }
+
+    Ok(routing)
+}
+
+/// Read from `TLS_CERT_PATH` and `TLS_KEY_PATH`, which have to be set together
+fn tls() -> color_eyre::Result<Option<TlsConfig>> {
+    match (
+        var_optional("TLS_CERT_PATH")?,
+        var_optional("TLS_KEY_PATH")?,
+    ) {
+        (Some(cert_path), Some(key_path)) => Ok(Some(TlsConfig {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+        })),
+        (None, None) => Ok(None),
+        _ => bail!("TLS_CERT_PATH and TLS_KEY_PATH must either both be set or both be unset"),
+    }
+}
+
+fn var_optional(name: &str) -> color_eyre::Result<Option<String>> {
+    match std::env::var(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(error) => Err(error).wrap_err_with(|| format!("unable to read {name} env var")),
+    }
+}
+
+fn var(name: &str) -> color_eyre::Result<String> {
+    std::env::var(name).wrap_err_with(|| format!("unable to read {name} env var"))
+}
+
+fn var_or<T>(name: &str, default: T) -> color_eyre::Result<T>
+where
+    T: FromStr,
+    T::Err: Error + Send + Sync + 'static,
+{
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .wrap_err_with(|| format!("unable to parse {name} env var")),
+        Err(VarError::NotPresent) => Ok(default),
+        Err(error) => Err(error).wrap_err_with(|| format!("unable to read {name} env var")),
+    }
+}
+
+/// Like [`var_or`], but rejects zero
+fn var_or_nonzero<T>(name: &str, default: T) -> color_eyre::Result<T>
+where
+    T: FromStr + Default + PartialEq,
+    T::Err: Error + Send + Sync + 'static,
+{
+    let value = var_or(name, default)?;
+
+    if value == T::default() {
+        bail!("{name} env var must not be zero");
+    }
+
+    Ok(value)
+}
+
+impl Config {
+    pub fn from_env() -> color_eyre::Result<Self> {
+        let tls = tls()?;
+        let unix_socket_path = var_optional("UNIX_SOCKET_PATH")?.map(PathBuf::from);
+
+        if tls.is_some() && unix_socket_path.is_some() {
+            bail!("UNIX_SOCKET_PATH cannot be combined with TLS_CERT_PATH and TLS_KEY_PATH");
+        }
+
+        Ok(Self {
+            hostname: var("HOSTNAME")?,
+            youtube_playlist_routing: playlist_routing()?,
+            database_url: var("DATABASE_URL")?,
+
+            google_client_id: var("GOOGLE_CLIENT_ID")?,
+            google_client_secret: var("GOOGLE_CLIENT_SECRET")?,
+
+            smtp_host: var_or("ALERTS_SMTP_HOST", "smtp.fastmail.com".to_owned())?,
+            smtp_port: var_or("ALERTS_SMTP_PORT", 465)?,
+            smtp_username: var("ALERTS_SMTP_USERNAME")?,
+            smtp_password: var("ALERTS_SMTP_PASSWORD")?,
+
+            shutdown_timeout_secs: var_or("SHUTDOWN_TIMEOUT_SECS", 30)?,
+            video_age_minutes_threshold: var_or("VIDEO_AGE_MINUTES_THRESHOLD", 1.0)?,
+            // The rate limiter panics or stalls forever on a zero rate or period
+            http_rate_limit_count: var_or_nonzero("HTTP_RATE_LIMIT_COUNT", 5)?,
+            http_rate_limit_period_secs: var_or_nonzero("HTTP_RATE_LIMIT_PERIOD_SECS", 10)?,
+            pubsub_verify_mode: match var_or("PUBSUB_VERIFY_MODE", "sync".to_owned())?.as_str() {
+                "sync" => Verify::Synchronous,
+                "async" => Verify::Asynchronous,
+                mode => bail!("unknown PUBSUB_VERIFY_MODE {mode:?}, expected sync or async"),
+            },
+            pubsub_allowed_ip_ranges: match std::env::var("PUBSUB_ALLOWED_IP_RANGES") {
+                Ok(ranges) => Some(
+                    ranges
+                        .split(',')
+                        .map(|range| range.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .wrap_err("unable to parse PUBSUB_ALLOWED_IP_RANGES env var")?,
+                ),
+                Err(VarError::NotPresent) => None,
+                Err(error) => {
+                    return Err(error).wrap_err("unable to read PUBSUB_ALLOWED_IP_RANGES env var");
+                }
+            },
+            max_concurrent_subscriptions: var_or("MAX_CONCURRENT_SUBSCRIPTIONS", 10)?,
+            max_concurrent_videos: var_or("MAX_CONCURRENT_VIDEOS", 5)?,
+            actor_health_timeout_secs: var_or("ACTOR_HEALTH_TIMEOUT_SECS", 3600)?,
+            smtp_max_retries: var_or("ALERTS_SMTP_MAX_RETRIES", 3)?,
+            // Every action would be left as a dead letter without being attempted
+            subscription_max_retries: var_or_nonzero("SUBSCRIPTION_MAX_RETRIES", 5)?,
+            subscription_queue_max_rows: var_or("SUBSCRIPTION_QUEUE_MAX_ROWS", 10000)?,
+            webhook_url: var_optional("WEBHOOK_URL")?,
+            inactive_channel_days: var_or("INACTIVE_CHANNEL_DAYS", 365)?,
+            video_queue_retention_days: var_or("VIDEO_QUEUE_RETENTION_DAYS", 90)?,
+            playlist_remove_shorts: var_or("PLAYLIST_REMOVE_SHORTS", false)?,
+            reqwest_pool_stats: var_or("REQWEST_POOL_STATS", false)?,
+            tls,
+            unix_socket_path,
+            bind_address: var_or("BIND_ADDRESS", SocketAddr::from(([127, 0, 0, 1], 8080)))?,
+        })
+    }
+
+    /// Url that the pubsubhubbub hub sends verifications and new uploads to
+    pub fn pubsub_callback(&self) -> String {
+        format!("https://{}/pubsub", self.hostname)
+    }
+
+    /// Playlist that new videos from the channel are added to
+    pub fn playlist_id_for(&self, channel_id: &str) -> &str {
+        self.youtube_playlist_routing
+            .get(channel_id)
+            .map_or_else(|| self.default_playlist_id(), String::as_str)
+    }
+
+    /// Playlist for channels without their own routing
+    pub fn default_playlist_id(&self) -> &str {
+        &self.youtube_playlist_routing[PLAYLIST_ROUTING_FALLBACK]
+    }
+
+    /// Every playlist that videos are routed to
+    pub fn playlist_ids(&self) -> BTreeSet<&str> {
+        self.youtube_playlist_routing
+            .values()
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+
+    pub fn actor_health_timeout(&self) -> Duration {
+        Duration::from_secs(self.actor_health_timeout_secs)
+    }
+
+    /// Channels whose last video is older than this are inactive
+    pub fn inactive_channel_threshold(&self) -> SignedDuration {
+        SignedDuration::from_hours(24 * i64::from(self.inactive_channel_days))
+    }
+
+    pub fn video_queue_retention(&self) -> SignedDuration {
+        SignedDuration::from_hours(24 * i64::from(self.video_queue_retention_days))
+    }
+
+    pub fn http_rate_limit_period(&self) -> Duration {
+        Duration::from_secs(self.http_rate_limit_period_secs)
+    }
+
+    pub fn pool_stats_enabled(&self) -> bool {
+        cfg!(debug_assertions) || self.reqwest_pool_stats
+    }
+}
@@ -1,29 +1,234 @@
-use std::{collections::HashSet, error::Error};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    time::Duration,
+};
 
 use entity::{
-    SubscriptionQueueToActiveSubscriptions, active_subscriptions, known_channels, o_auth,
-    subscription_queue, subscription_queue_result, video_queue,
+    SubscriptionQueueToActiveSubscriptions, active_subscriptions, actor_runs, audit_log,
+    channel_group_members, channel_groups, failed_feed_parses, known_channels, maintenance_windows,
+    o_auth, shorts_detection_cache, subscription_queue, subscription_queue_result,
+    subscription_refresh_state, subscription_sync_runs, video_blacklist, video_queue,
+    video_queue_result, youtube_api_calls,
 };
 use entity_types::{
-    jiff_compat::JiffTimestampMilliseconds, subscription_queue::SubscriptionAction,
+    JiffSignedDurationSeconds, JiffTimestampMilliseconds,
+    subscription_queue::{SubscriptionAction, SubscriptionSource},
+    video_queue::{DetectionMethod, ShortsDetectionMethod, VideoAction},
 };
-use jiff::Timestamp;
+use jiff::{SignedDuration, Timestamp};
 use migration::OnConflict;
 use sea_orm::{
-    ActiveValue, ColumnTrait as _, DatabaseConnection, DbErr, EntityTrait as _, IntoActiveModel,
-    Iterable, QueryFilter, QuerySelect,
+    ActiveValue, ColumnTrait as _, Condition, DatabaseConnection, DatabaseTransaction, DbErr,
+    EntityTrait as _, IntoActiveModel, Iterable, PaginatorTrait as _, QueryFilter, QueryOrder,
+    QuerySelect, TransactionTrait as _,
+    sea_query::{Expr, Func, LikeExpr},
 };
+use serde::{Deserialize, Serialize};
 use tokio::sync::Notify;
 
 use crate::feed;
 
+#[cfg(test)]
+mod test {
+    use entity::{known_channels, subscription_queue};
+    use entity_types::subscription_queue::{SubscriptionAction, SubscriptionSource};
+    use migration::{Migrator, MigratorTrait as _};
+    use sea_orm::{Database, DbErr, EntityTrait as _, QueryOrder as _};
+    use tokio::sync::Notify;
+
+    use crate::database::{
+        DEFAULT_PRIORITY, KnownChannels, SUBSCRIPTION_RETRY_DELAY, SUBSCRIPTION_RETRY_DELAY_CAP,
+        SubscriptionQueue, subscription_retry_delay,
+    };
+
+    #[test]
+    fn test_subscription_retry_delay_bounds() {
+        for retry_count in 0..20i32 {
+            let max_delay = SUBSCRIPTION_RETRY_DELAY
+                .checked_mul(2i32.pow(retry_count.unsigned_abs()))
+                .unwrap()
+                .min(SUBSCRIPTION_RETRY_DELAY_CAP);
+
+            for _ in 0..100 {
+                let delay = subscription_retry_delay(retry_count);
+                assert!(!delay.is_negative(), "{delay} should not be negative");
+                assert!(delay <= max_delay, "{delay} should be at most {max_delay}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_subscription_retry_delay_cap() {
+        for retry_count in [31, 100, i32::MAX] {
+            for _ in 0..100 {
+                let delay = subscription_retry_delay(retry_count);
+                assert!(!delay.is_negative(), "{delay} should not be negative");
+                assert!(
+                    delay <= SUBSCRIPTION_RETRY_DELAY_CAP,
+                    "{delay} should be capped at an hour"
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_confirm_secret_of_sent_request() -> Result<(), DbErr> {
+        let database = Database::connect("sqlite::memory:").await?;
+        Migrator::up(&database, None).await?;
+
+        let channel_id = "UCaaaaaaaaaaaaaaaaaaaaaa";
+        KnownChannels::add_channels(
+            &database,
+            [known_channels::Model {
+                channel_id: channel_id.to_owned(),
+                channel_name: "Channel".to_owned(),
+                channel_profile_picture: String::new(),
+                is_shorts_channel: false,
+                video_age_threshold_minutes: None,
+                last_video_at: None,
+                handle: None,
+                shorts_ratio: None,
+                shorts_ratio_samples: 0,
+                notifications_paused: false,
+                subscription_count: None,
+                subscriber_count: None,
+                priority: DEFAULT_PRIORITY,
+                metadata_updated_at: None,
+                profile_picture_updated_at: None,
+            }],
+        )
+        .await?;
+
+        // A forced subscription is queued with a new secret while the first one is in flight
+        for action in [
+            SubscriptionAction::Subscribe,
+            SubscriptionAction::ForceSubscribe,
+        ] {
+            SubscriptionQueue::add_actions(
+                &database,
+                &Notify::new(),
+                10,
+                5,
+                [(channel_id.to_owned(), action)],
+                SubscriptionSource::AdminManual,
+            )
+            .await?;
+        }
+
+        let [subscribe, force_subscribe] = &subscription_queue::Entity::find()
+            .order_by_asc(subscription_queue::Column::Id)
+            .all(&database)
+            .await?[..]
+        else {
+            panic!("exactly two actions should be queued");
+        };
+        assert_ne!(subscribe.secret, force_subscribe.secret);
+
+        SubscriptionQueue::mark_sent(&database, subscribe.id).await?;
+        assert_eq!(
+            SubscriptionQueue::confirm_secret(&database, channel_id).await?,
+            subscribe.secret
+        );
+
+        SubscriptionQueue::mark_sent(&database, force_subscribe.id).await?;
+        assert_eq!(
+            SubscriptionQueue::confirm_secret(&database, channel_id).await?,
+            force_subscribe.secret
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoQueueStatus {
+    /// Not processed yet
+    Pending,
+    /// Processed without failing, including skipped videos
+    Processed,
+    Failed,
+}
+
+/// Priority of channels that have not been given one, lower values are processed first
+pub const DEFAULT_PRIORITY: i32 = 5;
+
+/// Videos that have been processing for longer than this are assumed to have been abandoned
+const PROCESSING_TIMEOUT: SignedDuration = SignedDuration::from_mins(5);
+
+/// Number of rows deleted by each statement when cleaning up the queue
+const DELETE_BATCH_SIZE: usize = 500;
+
+/// Log the sql of a query and, on sqlite, how it will be run, to spot missing indexes during development
+#[cfg(debug_assertions)]
+async fn explain_query(db: &DatabaseConnection, query: &impl sea_orm::QueryTrait) {
+    use sea_orm::{ConnectionTrait as _, DatabaseBackend, Statement};
+
+    let backend = db.get_database_backend();
+    let statement = query.build(backend);
+    tracing::trace!(sql = statement.sql, "built query");
+
+    if backend != DatabaseBackend::Sqlite {
+        return;
+    }
+
+    let explain = Statement {
+        sql: format!("EXPLAIN QUERY PLAN {}", statement.sql),
+        ..statement
+    };
+
+    match db.query_all(explain).await {
+        Ok(rows) => {
+            let plan = rows
+                .iter()
+                .filter_map(|row| row.try_get::<String>("", "detail").ok())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            tracing::debug!(%plan, "query plan");
+        }
+        Err(error) => tracing::warn!(%error, "failed to explain query"),
+    }
+}
+
 pub struct VideoQueue;
 
 impl VideoQueue {
-    pub async fn new_video(db: &DatabaseConnection, entry: feed::Entry) -> Result<(), DbErr> {
-        video_queue::Entity::insert(video_queue::ActiveModel {
+    /// Returns `false` if the video was already in the queue
+    ///
+    /// `video_age_minutes_threshold` is used for channels without their own threshold, to tell new
+    /// uploads apart from updates to old videos
+    pub async fn new_video(
+        db: &DatabaseConnection,
+        entry: feed::Entry,
+        sender_ip: Option<String>,
+        target_playlist_id: &str,
+        video_age_minutes_threshold: f64,
+    ) -> Result<bool, DbErr> {
+        // Denormalized for display, so it is fine if the channel is renamed later. The priority is
+        // copied so that changing it does not reorder videos that are already queued
+        let (channel_name, priority, channel_video_age_threshold_minutes) =
+            known_channels::Entity::find_by_id(&entry.channel_id)
+                .select_only()
+                .column(known_channels::Column::ChannelName)
+                .column(known_channels::Column::Priority)
+                .column(known_channels::Column::VideoAgeThresholdMinutes)
+                .into_tuple::<(String, i32, Option<f64>)>()
+                .one(db)
+                .await?
+                .unwrap_or((String::new(), DEFAULT_PRIORITY, None));
+
+        let video_age_minutes = entry.published.duration_until(entry.updated).as_secs_f64() / 60.0;
+        let is_new_upload = video_age_minutes
+            <= channel_video_age_threshold_minutes.unwrap_or(video_age_minutes_threshold);
+
+        let is_short_hint = entry.is_short_hint();
+        let channel_id = entry.channel_id;
+        let rows_affected = video_queue::Entity::insert(video_queue::ActiveModel {
             id: ActiveValue::NotSet,
-            channel_id: ActiveValue::Set(entry.channel_id),
+            channel_id: ActiveValue::Set(channel_id.clone()),
+            channel_name: ActiveValue::Set(channel_name),
             video_id: ActiveValue::Set(entry.video_id),
 
             title: ActiveValue::Set(entry.title),
@@ -32,8 +237,368 @@ impl VideoQueue {
             updated_at: ActiveValue::Set(JiffTimestampMilliseconds(entry.updated)),
 
             timestamp: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+            sender_ip: ActiveValue::Set(sender_ip),
+            processing_since: ActiveValue::Set(None),
+            priority: ActiveValue::Set(priority),
+            is_short_hint: ActiveValue::Set(is_short_hint),
+            target_playlist_id: ActiveValue::Set(Some(target_playlist_id.to_owned())),
         })
-        .exec(db)
+        .on_conflict(
+            OnConflict::column(video_queue::Column::VideoId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        if rows_affected == 0 {
+            return Ok(false);
+        }
+
+        // Updates to old videos are skipped when processed, so they do not count as activity
+        if is_new_upload {
+            KnownChannels::update_last_video_at(db, &channel_id, Timestamp::now()).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Includes videos whose processing was started too long ago without finishing, ordered by
+    /// priority and then the order they were queued in
+    pub async fn get_pending_videos(db: &DatabaseConnection) -> Result<Vec<VideoQueueItem>, DbErr> {
+        let stale_before = Timestamp::now() - PROCESSING_TIMEOUT;
+
+        let query = video_queue::Entity::find()
+            .left_join(video_queue_result::Entity)
+            .filter(video_queue_result::Column::Timestamp.is_null())
+            .filter(video_queue::Column::ProcessingSince.is_null().or(
+                video_queue::Column::ProcessingSince.lt(JiffTimestampMilliseconds(stale_before)),
+            ))
+            .order_by_asc(video_queue::Column::Priority)
+            .order_by_asc(video_queue::Column::Id)
+            .find_also_related(known_channels::Entity);
+
+        #[cfg(debug_assertions)]
+        explain_query(db, &query).await;
+
+        Ok(query
+            .all(db) // TODO: paginate?
+            .await?
+            .into_iter()
+            .map(|(queue_item, channel)| VideoQueueItem {
+                queue_item,
+                channel,
+                db: db.clone(),
+            })
+            .collect())
+    }
+
+    pub async fn mark_processing(db: &DatabaseConnection, id: i32) -> Result<(), DbErr> {
+        video_queue::Entity::update_many()
+            .col_expr(
+                video_queue::Column::ProcessingSince,
+                Expr::value(JiffTimestampMilliseconds(Timestamp::now())),
+            )
+            .filter(video_queue::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Clear processing marks left behind by a previous run, returning how many were cleared
+    pub async fn clear_stale_locks(
+        db: &DatabaseConnection,
+        older_than: Timestamp,
+    ) -> Result<u64, DbErr> {
+        let result = video_queue::Entity::update_many()
+            .col_expr(
+                video_queue::Column::ProcessingSince,
+                Expr::value(Option::<JiffTimestampMilliseconds>::None),
+            )
+            .filter(video_queue::Column::ProcessingSince.lt(JiffTimestampMilliseconds(older_than)))
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    pub async fn get_processing_stats(
+        db: &DatabaseConnection,
+        since: Timestamp,
+    ) -> Result<VideoProcessingStats, DbErr> {
+        let counts = video_queue::Entity::find()
+            .left_join(video_queue_result::Entity)
+            .select_only()
+            .column(video_queue_result::Column::Action)
+            .column_as(video_queue::Column::Id.count(), "count")
+            .filter(video_queue::Column::Timestamp.gte(JiffTimestampMilliseconds(since)))
+            .group_by(video_queue_result::Column::Action)
+            .into_tuple::<(Option<VideoAction>, i64)>()
+            .all(db)
+            .await?;
+
+        let mut stats = VideoProcessingStats::default();
+        for (action, count) in counts {
+            let count = u64::try_from(count).unwrap_or_default();
+            stats.total += count;
+
+            match action {
+                None => stats.pending += count,
+                Some(VideoAction::Inserted) => stats.inserted += count,
+                Some(VideoAction::SkippedShort) => stats.skipped_short += count,
+                Some(VideoAction::SkippedDuplicate) => stats.skipped_duplicate += count,
+                Some(VideoAction::Failed) => stats.failed += count,
+                // Only counted in the total
+                Some(
+                    VideoAction::SkippedOld
+                    | VideoAction::SkippedUnknownChannel
+                    | VideoAction::SkippedNotPublic,
+                ) => {}
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Videos that have not been processed yet
+    pub async fn count_pending(db: &DatabaseConnection) -> Result<u64, DbErr> {
+        video_queue::Entity::find()
+            .left_join(video_queue_result::Entity)
+            .filter(video_queue_result::Column::QueueId.is_null())
+            .count(db)
+            .await
+    }
+
+    /// Processed videos that were queued before `before`. Unprocessed videos are never counted, no
+    /// matter how old
+    pub async fn count_processed_before(
+        db: &DatabaseConnection,
+        before: Timestamp,
+    ) -> Result<u64, DbErr> {
+        video_queue::Entity::find()
+            .inner_join(video_queue_result::Entity)
+            .filter(video_queue::Column::Timestamp.lt(JiffTimestampMilliseconds(before)))
+            .count(db)
+            .await
+    }
+
+    /// Delete processed videos, and their results, that were queued before `before`, returning how
+    /// many were deleted
+    pub async fn delete_processed_before(
+        db: &DatabaseConnection,
+        before: Timestamp,
+    ) -> Result<u64, DbErr> {
+        let transaction = db.begin().await?;
+
+        let ids = video_queue::Entity::find()
+            .inner_join(video_queue_result::Entity)
+            .select_only()
+            .column(video_queue::Column::Id)
+            .filter(video_queue::Column::Timestamp.lt(JiffTimestampMilliseconds(before)))
+            .into_tuple::<i32>()
+            .all(&transaction)
+            .await?;
+
+        // Kept well below sqlite's limit on bound parameters
+        for ids in ids.chunks(DELETE_BATCH_SIZE) {
+            // Results first, since they reference the queue
+            video_queue_result::Entity::delete_many()
+                .filter(video_queue_result::Column::QueueId.is_in(ids.iter().copied()))
+                .exec(&transaction)
+                .await?;
+
+            video_queue::Entity::delete_many()
+                .filter(video_queue::Column::Id.is_in(ids.iter().copied()))
+                .exec(&transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(ids.len() as u64)
+    }
+
+    /// Page through the queue, newest first
+    pub async fn get_page(
+        db: &DatabaseConnection,
+        status: Option<VideoQueueStatus>,
+        channel_id: Option<&str>,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<(video_queue::Model, Option<video_queue_result::Model>)>, DbErr> {
+        let mut query = video_queue::Entity::find().find_also_related(video_queue_result::Entity);
+
+        if let Some(channel_id) = channel_id {
+            query = query.filter(video_queue::Column::ChannelId.eq(channel_id));
+        }
+
+        query = match status {
+            None => query,
+            Some(VideoQueueStatus::Pending) => {
+                query.filter(video_queue_result::Column::QueueId.is_null())
+            }
+            Some(VideoQueueStatus::Processed) => {
+                query.filter(video_queue_result::Column::Action.ne(VideoAction::Failed))
+            }
+            Some(VideoQueueStatus::Failed) => {
+                query.filter(video_queue_result::Column::Action.eq(VideoAction::Failed))
+            }
+        };
+
+        query
+            .order_by_desc(video_queue::Column::Id)
+            .limit(limit)
+            .offset(offset)
+            .all(db)
+            .await
+    }
+
+    /// Every video that has been inserted into the playlist
+    ///
+    /// Videos inserted before playlist routing do not have a playlist, and are assumed to
+    /// have been inserted into `default_playlist_id`
+    pub async fn get_inserted_video_ids(
+        db: &DatabaseConnection,
+        playlist_id: &str,
+        default_playlist_id: &str,
+    ) -> Result<HashSet<String>, DbErr> {
+        Ok(video_queue::Entity::find()
+            .select_only()
+            .column(video_queue::Column::VideoId)
+            .inner_join(video_queue_result::Entity)
+            .filter(video_queue_result::Column::Action.eq(VideoAction::Inserted))
+            .filter(
+                Expr::expr(Func::coalesce([
+                    Expr::col(video_queue_result::Column::PlaylistId).into(),
+                    Expr::value(default_playlist_id),
+                ]))
+                .eq(playlist_id),
+            )
+            .into_tuple::<String>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    /// Videos from the channel with how they were processed, newest first
+    pub async fn get_video_history_for_channel(
+        db: &DatabaseConnection,
+        channel_id: &str,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<VideoHistoryEntry>, DbErr> {
+        let videos = video_queue::Entity::find()
+            .find_also_related(video_queue_result::Entity)
+            .filter(video_queue::Column::ChannelId.eq(channel_id))
+            .order_by_desc(video_queue::Column::PublishedAt)
+            .limit(limit)
+            .offset(offset)
+            .all(db)
+            .await?;
+
+        Ok(videos
+            .into_iter()
+            .map(|(video, result)| VideoHistoryEntry {
+                video_id: video.video_id,
+                title: video.title,
+                published_at: video.published_at.0,
+                queued_at: video.timestamp.0,
+                action: result.as_ref().map(|result| result.action),
+                shorts_redirect: result.as_ref().map(|result| result.shorts_redirect),
+                error_message: result
+                    .as_ref()
+                    .and_then(|result| result.error_message.clone()),
+                processed_at: result.map(|result| result.timestamp.0),
+            })
+            .collect())
+    }
+}
+
+/// A video from the queue and the outcome of processing it
+#[derive(Debug, Serialize)]
+pub struct VideoHistoryEntry {
+    pub video_id: String,
+    pub title: String,
+    pub published_at: Timestamp,
+    pub queued_at: Timestamp,
+
+    /// [`None`] if the video has not been processed yet
+    pub action: Option<VideoAction>,
+    pub shorts_redirect: Option<bool>,
+    pub error_message: Option<String>,
+    pub processed_at: Option<Timestamp>,
+}
+
+/// Counts of videos queued within a time range, grouped by how they were processed
+#[derive(Debug, Default, Serialize)]
+pub struct VideoProcessingStats {
+    pub total: u64,
+    pub pending: u64,
+    pub inserted: u64,
+    pub skipped_short: u64,
+    pub skipped_duplicate: u64,
+    pub failed: u64,
+}
+
+pub struct VideoQueueItem {
+    queue_item: video_queue::Model,
+    channel: Option<known_channels::Model>,
+    db: DatabaseConnection,
+}
+
+pub struct ProcessedVideo {
+    pub action: VideoAction,
+    pub shorts_redirect: bool,
+    /// [`None`] if the video was skipped before shorts detection finished
+    pub shorts_detection_method: Option<ShortsDetectionMethod>,
+    /// Playlist that the video was routed to, [`None`] if it was skipped before routing
+    pub playlist_id: Option<String>,
+    /// Id of the playlist item, needed to remove the video from the playlist, [`None`] unless
+    /// the video was inserted
+    pub playlist_item_id: Option<String>,
+    /// Why processing failed, [`None`] unless the action is [`VideoAction::Failed`]
+    pub error_message: Option<String>,
+    /// Privacy status reported by the youtube api, [`None`] if it was not fetched
+    pub visibility: Option<String>,
+}
+
+impl VideoQueueItem {
+    pub async fn process<F>(self, function: F) -> Result<(), DbErr>
+    where
+        F: AsyncFnOnce(&video_queue::Model, Option<&known_channels::Model>) -> ProcessedVideo
+            + Send
+            + Sync,
+    {
+        VideoQueue::mark_processing(&self.db, self.queue_item.id).await?;
+
+        let ProcessedVideo {
+            action,
+            shorts_redirect,
+            shorts_detection_method,
+            playlist_id,
+            playlist_item_id,
+            error_message,
+            visibility,
+        } = function(&self.queue_item, self.channel.as_ref()).await;
+
+        video_queue_result::Entity::insert(
+            video_queue_result::Model {
+                queue_id: self.queue_item.id,
+                action,
+                shorts_redirect,
+                visibility: visibility.unwrap_or_else(|| "unknown".to_owned()),
+                duration: JiffSignedDurationSeconds(SignedDuration::ZERO),
+                timestamp: JiffTimestampMilliseconds(Timestamp::now()),
+                playlist_id,
+                shorts_detection_method,
+                playlist_item_id,
+                error_message,
+            }
+            .into_active_model(),
+        )
+        .exec(&self.db)
         .await?;
 
         Ok(())
@@ -55,11 +620,13 @@ impl ActiveSubscriptions {
         db: &DatabaseConnection,
         channel_id: String,
         expiration: Timestamp,
+        secret: Option<String>,
     ) -> Result<(), DbErr> {
         active_subscriptions::Entity::insert(
             active_subscriptions::Model {
                 channel_id: channel_id.to_owned(),
                 expiration: JiffTimestampMilliseconds(expiration),
+                secret,
             }
             .into_active_model(),
         )
@@ -74,6 +641,15 @@ impl ActiveSubscriptions {
         Ok(())
     }
 
+    pub async fn get_subscription(
+        db: &DatabaseConnection,
+        channel_id: &str,
+    ) -> Result<Option<active_subscriptions::Model>, DbErr> {
+        active_subscriptions::Entity::find_by_id(channel_id)
+            .one(db)
+            .await
+    }
+
     pub async fn get_soonest_expiration(
         db: &DatabaseConnection,
     ) -> Result<Option<Timestamp>, DbErr> {
@@ -100,6 +676,68 @@ impl ActiveSubscriptions {
             .await
     }
 
+    pub async fn get_subscription_stats(
+        db: &DatabaseConnection,
+    ) -> Result<SubscriptionStats, DbErr> {
+        let now = Timestamp::now();
+        let expiration = || Expr::col(active_subscriptions::Column::Expiration);
+        let count_expiring_before = |before: Timestamp| {
+            Expr::expr(Func::sum(
+                Expr::case(
+                    expiration()
+                        .gte(JiffTimestampMilliseconds(now))
+                        .and(expiration().lt(JiffTimestampMilliseconds(before))),
+                    1,
+                )
+                .finally(0),
+            ))
+        };
+
+        let (total, expiring_in_1_day, expiring_in_7_days, already_expired, newest, oldest) =
+            active_subscriptions::Entity::find()
+                .select_only()
+                .column_as(active_subscriptions::Column::ChannelId.count(), "total")
+                .column_as(
+                    count_expiring_before(now + SignedDuration::from_hours(24)),
+                    "expiring_in_1_day",
+                )
+                .column_as(
+                    count_expiring_before(now + SignedDuration::from_hours(24 * 7)),
+                    "expiring_in_7_days",
+                )
+                .column_as(
+                    Expr::expr(Func::sum(
+                        Expr::case(expiration().lt(JiffTimestampMilliseconds(now)), 1).finally(0),
+                    )),
+                    "already_expired",
+                )
+                .column_as(active_subscriptions::Column::Expiration.max(), "newest")
+                .column_as(active_subscriptions::Column::Expiration.min(), "oldest")
+                .into_tuple::<(
+                    i64,
+                    Option<i64>,
+                    Option<i64>,
+                    Option<i64>,
+                    Option<JiffTimestampMilliseconds>,
+                    Option<JiffTimestampMilliseconds>,
+                )>()
+                .one(db)
+                .await?
+                .unwrap_or_default();
+
+        let count =
+            |count: Option<i64>| u64::try_from(count.unwrap_or_default()).unwrap_or_default();
+
+        Ok(SubscriptionStats {
+            total: count(Some(total)),
+            expiring_in_1_day: count(expiring_in_1_day),
+            expiring_in_7_days: count(expiring_in_7_days),
+            already_expired: count(already_expired),
+            newest_expiration: newest.map(|newest| newest.0),
+            oldest_expiration: oldest.map(|oldest| oldest.0),
+        })
+    }
+
     pub async fn get_all_channel_ids(db: &DatabaseConnection) -> Result<HashSet<String>, DbErr> {
         let all_entities = active_subscriptions::Entity::find()
             .select_only()
@@ -112,22 +750,102 @@ impl ActiveSubscriptions {
     }
 }
 
+fn generate_secret() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+/// Pending actions are processed in ascending priority, so that unsubscriptions free up
+/// room for new subscriptions before they are made
+fn priority(action: SubscriptionAction) -> i32 {
+    match action {
+        SubscriptionAction::Unsubscribe => 1,
+        SubscriptionAction::Subscribe
+        | SubscriptionAction::ForceSubscribe
+        | SubscriptionAction::PauseNotifications
+        | SubscriptionAction::ResumeNotifications => 5,
+        SubscriptionAction::Refresh => 10,
+    }
+}
+
+/// Overview of the active subscriptions' expirations
+#[derive(Debug, Default, Serialize)]
+pub struct SubscriptionStats {
+    pub total: u64,
+    /// Not yet expired, but will be within a day
+    pub expiring_in_1_day: u64,
+    /// Not yet expired, but will be within a week, including those expiring within a day
+    pub expiring_in_7_days: u64,
+    pub already_expired: u64,
+    pub newest_expiration: Option<Timestamp>,
+    pub oldest_expiration: Option<Timestamp>,
+}
+
 pub struct SubscriptionQueue;
 
 impl SubscriptionQueue {
+    /// Queue the actions, generating a hub secret for subscriptions if the channel does not have one already
+    /// Fails with [`DbErr::Custom`] if queueing the actions would leave more than `max_rows`
+    /// actions waiting to be processed
     pub async fn add_actions(
         db: &DatabaseConnection,
         notify: &Notify,
+        max_rows: u64,
+        max_retries: u32,
         actions: impl IntoIterator<Item = (String, SubscriptionAction)>, // TODO: newtype channel id and other ids
+        source: SubscriptionSource,
     ) -> Result<(), DbErr> {
+        let actions = Vec::from_iter(actions);
+
+        if !actions.is_empty() {
+            let pending =
+                Self::check_capacity(db, max_rows, max_retries, actions.len() as u64).await?;
+
+            if Self::is_nearly_full(pending, max_rows) {
+                tracing::warn!(pending, max_rows, "subscription queue is nearly full");
+            }
+        }
+
+        let existing_secrets: HashMap<String, String> = active_subscriptions::Entity::find()
+            .select_only()
+            .column(active_subscriptions::Column::ChannelId)
+            .column(active_subscriptions::Column::Secret)
+            .filter(active_subscriptions::Column::Secret.is_not_null())
+            .into_tuple()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
         subscription_queue::Entity::insert_many(actions.into_iter().map(|(channel_id, action)| {
+            let secret = match action {
+                SubscriptionAction::Subscribe | SubscriptionAction::Refresh => Some(
+                    existing_secrets
+                        .get(&channel_id)
+                        .cloned()
+                        .unwrap_or_else(generate_secret),
+                ),
+                SubscriptionAction::ForceSubscribe => Some(generate_secret()),
+                // Unsubscribed channels no longer need a secret
+                SubscriptionAction::Unsubscribe
+                | SubscriptionAction::PauseNotifications
+                | SubscriptionAction::ResumeNotifications => None,
+            };
+
             subscription_queue::ActiveModel {
                 id: ActiveValue::NotSet,
                 channel_id: ActiveValue::Set(channel_id),
                 action: ActiveValue::Set(action),
                 timestamp: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+                secret: ActiveValue::Set(secret),
+                scheduled_for: ActiveValue::Set(None),
+                priority: ActiveValue::Set(priority(action)),
+                source: ActiveValue::Set(source),
+                retry_count: ActiveValue::Set(0),
+                sent_at: ActiveValue::Set(None),
+                confirmed_at: ActiveValue::Set(None),
             }
         }))
+        .on_empty_do_nothing()
         .exec(db)
         .await?;
 
@@ -137,83 +855,679 @@ impl SubscriptionQueue {
         Ok(())
     }
 
-    pub async fn get_pending_actions(
+    /// Number of actions that would be waiting to be processed after queueing `additional` more
+    ///
+    /// Fails with [`DbErr::Custom`] if that would be more than `max_rows`
+    pub async fn check_capacity(
         db: &DatabaseConnection,
-    ) -> Result<Vec<SubscriptionQueueItem>, DbErr> {
-        Ok(subscription_queue::Entity::find()
-            .left_join(subscription_queue_result::Entity)
-            .filter(subscription_queue_result::Column::Timestamp.is_null())
-            .find_also_linked(SubscriptionQueueToActiveSubscriptions)
-            .all(db) // TODO: paginate?
-            .await?
-            .into_iter()
-            .map(|(queue_item, active_subscription)| SubscriptionQueueItem {
-                queue_item,
-                active_subscription,
-                db: db.clone(),
-            })
-            .collect())
+        max_rows: u64,
+        max_retries: u32,
+        additional: u64,
+    ) -> Result<u64, DbErr> {
+        let pending = Self::count_all_pending(db, max_retries).await? + additional;
+
+        if pending > max_rows {
+            tracing::warn!(
+                pending,
+                max_rows,
+                "subscription queue is full, rejecting actions"
+            );
+            return Err(DbErr::Custom(QUEUE_FULL.to_string()));
+        }
+
+        Ok(pending)
     }
-}
 
-pub struct SubscriptionQueueItem {
-    queue_item: subscription_queue::Model,
-    active_subscription: Option<active_subscriptions::Model>,
-    db: DatabaseConnection,
-}
+    /// The error returned by [`SubscriptionQueue::check_capacity`] when the queue is full
+    pub fn is_queue_full(error: &DbErr) -> bool {
+        matches!(error, DbErr::Custom(message) if message == QUEUE_FULL)
+    }
 
-impl SubscriptionQueueItem {
-    pub async fn process<F, E>(self, function: F) -> Result<(), DbErr>
-    where
-        F: AsyncFnOnce(
-                &subscription_queue::Model,
-                Option<&active_subscriptions::Model>,
-            ) -> Result<(), E>
-            + Send
-            + Sync,
-        E: Error + Send + Sync,
-    {
-        let result = function(&self.queue_item, self.active_subscription.as_ref()).await;
+    /// At least 80% of the rows allowed by `max_rows` are waiting to be processed
+    pub fn is_nearly_full(pending: u64, max_rows: u64) -> bool {
+        pending.saturating_mul(5) >= max_rows.saturating_mul(4)
+    }
 
-        let model = match result {
-            Ok(()) => subscription_queue_result::Model {
-                queue_id: self.queue_item.id,
-                error: None,
-                timestamp: JiffTimestampMilliseconds(Timestamp::now()),
-            },
-            Err(error) => {
-                // TODO: how to handle retries? do we just wait for the subscription manager?
-                tracing::error!(%error, "failed to process subscription queue item");
+    /// Queue a single action for a single channel, unless it is already waiting to be processed
+    ///
+    /// See [`SubscriptionQueue::add_actions`]
+    pub async fn add_single_action(
+        db: &DatabaseConnection,
+        notify: &Notify,
+        max_rows: u64,
+        max_retries: u32,
+        channel_id: String,
+        action: SubscriptionAction,
+        source: SubscriptionSource,
+    ) -> Result<(), DbErr> {
+        if Self::count_pending_for_channel(db, &channel_id).await? > 0 {
+            let latest = subscription_queue::Entity::find()
+                .left_join(subscription_queue_result::Entity)
+                .filter(subscription_queue_result::Column::Timestamp.is_null())
+                .filter(subscription_queue::Column::ChannelId.eq(&channel_id))
+                .order_by_desc(subscription_queue::Column::Id)
+                .one(db)
+                .await?;
 
-                subscription_queue_result::Model {
-                    queue_id: self.queue_item.id,
-                    error: Some(error.to_string()),
-                    timestamp: JiffTimestampMilliseconds(Timestamp::now()),
-                }
+            // Only the latest action counts, so that toggling back and forth is still queued.
+            // Retries may be scheduled far in the future, so do not wait for them.
+            if latest
+                .is_some_and(|latest| latest.action == action && latest.scheduled_for.is_none())
+            {
+                tracing::debug!(channel_id, ?action, "action is already pending");
+                return Ok(());
             }
-        };
+        }
+
+        Self::add_actions(
+            db,
+            notify,
+            max_rows,
+            max_retries,
+            [(channel_id, action)],
+            source,
+        )
+        .await
+    }
 
-        subscription_queue_result::Entity::insert(model.into_active_model())
-            .exec(&self.db)
+    /// Record that the action's request is about to be sent to the hub, so that its
+    /// verification can be matched to it by [`SubscriptionQueue::confirm_secret`]
+    pub async fn mark_sent(db: &DatabaseConnection, id: i32) -> Result<(), DbErr> {
+        subscription_queue::Entity::update_many()
+            .col_expr(
+                subscription_queue::Column::SentAt,
+                Expr::value(JiffTimestampMilliseconds(Timestamp::now())),
+            )
+            .filter(subscription_queue::Column::Id.eq(id))
+            .exec(db)
             .await?;
 
         Ok(())
     }
-}
 
-pub struct KnownChannels;
-
-impl KnownChannels {
-    pub async fn add_channels(
+    /// Secret sent to the hub by the subscription request that it is verifying, which is the
+    /// oldest request sent for the channel that has not been confirmed or failed yet
+    ///
+    /// Falls back to the secret of the active subscription if no request matches, such as when
+    /// the hub verifies a subscription again on its own
+    pub async fn confirm_secret(
         db: &DatabaseConnection,
-        channels: impl IntoIterator<Item = known_channels::Model>,
-    ) -> Result<(), DbErr> {
-        known_channels::Entity::insert_many(
-            channels.into_iter().map(IntoActiveModel::into_active_model),
+        channel_id: &str,
+    ) -> Result<Option<String>, DbErr> {
+        let transaction = db.begin().await?;
+
+        let request = subscription_queue::Entity::find()
+            .left_join(subscription_queue_result::Entity)
+            .filter(subscription_queue::Column::ChannelId.eq(channel_id))
+            .filter(subscription_queue::Column::Action.is_in([
+                SubscriptionAction::Subscribe,
+                SubscriptionAction::Refresh,
+                SubscriptionAction::ForceSubscribe,
+            ]))
+            .filter(subscription_queue::Column::ConfirmedAt.is_null())
+            .filter(
+                subscription_queue::Column::SentAt.gte(JiffTimestampMilliseconds(
+                    Timestamp::now() - VERIFICATION_WINDOW,
+                )),
+            )
+            // Still in flight with synchronous verification, or sent successfully otherwise
+            .filter(subscription_queue_result::Column::Error.is_null())
+            .order_by_asc(subscription_queue::Column::SentAt)
+            .order_by_asc(subscription_queue::Column::Id)
+            .one(&transaction)
+            .await?;
+
+        let secret = match request {
+            Some(request) => {
+                subscription_queue::Entity::update_many()
+                    .col_expr(
+                        subscription_queue::Column::ConfirmedAt,
+                        Expr::value(JiffTimestampMilliseconds(Timestamp::now())),
+                    )
+                    .filter(subscription_queue::Column::Id.eq(request.id))
+                    .exec(&transaction)
+                    .await?;
+
+                request.secret
+            }
+            None => {
+                tracing::debug!(channel_id, "verification does not match a sent request");
+
+                active_subscriptions::Entity::find_by_id(channel_id)
+                    .one(&transaction)
+                    .await?
+                    .and_then(|subscription| subscription.secret)
+            }
+        };
+
+        transaction.commit().await?;
+
+        Ok(secret)
+    }
+
+    pub async fn count_pending(
+        db: &DatabaseConnection,
+        action: SubscriptionAction,
+    ) -> Result<u64, DbErr> {
+        subscription_queue::Entity::find()
+            .left_join(subscription_queue_result::Entity)
+            .filter(subscription_queue_result::Column::Timestamp.is_null())
+            .filter(subscription_queue::Column::Action.eq(action))
+            .count(db)
+            .await
+    }
+
+    /// Actions of every kind that have not been processed yet, including retries scheduled for later
+    ///
+    /// Dead letters are left out, since they will never be processed unless they are requeued
+    pub async fn count_all_pending(
+        db: &DatabaseConnection,
+        max_retries: u32,
+    ) -> Result<u64, DbErr> {
+        subscription_queue::Entity::find()
+            .left_join(subscription_queue_result::Entity)
+            .filter(subscription_queue_result::Column::Timestamp.is_null())
+            .filter(subscription_queue::Column::RetryCount.lt(max_retries))
+            .count(db)
+            .await
+    }
+
+    /// Most recently queued actions and their results, newest first
+    pub async fn get_recent(
+        db: &DatabaseConnection,
+        limit: u64,
+    ) -> Result<
+        Vec<(
+            subscription_queue::Model,
+            Option<subscription_queue_result::Model>,
+        )>,
+        DbErr,
+    > {
+        subscription_queue::Entity::find()
+            .find_also_related(subscription_queue_result::Entity)
+            .order_by_desc(subscription_queue::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    pub async fn count_pending_for_channel(
+        db: &DatabaseConnection,
+        channel_id: &str,
+    ) -> Result<u64, DbErr> {
+        subscription_queue::Entity::find()
+            .left_join(subscription_queue_result::Entity)
+            .filter(subscription_queue_result::Column::Timestamp.is_null())
+            .filter(subscription_queue::Column::ChannelId.eq(channel_id))
+            .count(db)
+            .await
+    }
+
+    /// Number of each action queued per day (UTC) since the given time, ordered by day
+    pub async fn get_daily_action_counts(
+        db: &DatabaseConnection,
+        since: Timestamp,
+    ) -> Result<Vec<(String, SubscriptionAction, i64)>, DbErr> {
+        subscription_queue::Entity::find()
+            .select_only()
+            .column_as(
+                Expr::cust("date(\"timestamp\" / 1000, 'unixepoch')"),
+                "date",
+            )
+            .column(subscription_queue::Column::Action)
+            .column_as(subscription_queue::Column::Id.count(), "count")
+            .filter(subscription_queue::Column::Timestamp.gte(JiffTimestampMilliseconds(since)))
+            .group_by(Expr::cust("\"date\""))
+            .group_by(subscription_queue::Column::Action)
+            .order_by_asc(Expr::cust("\"date\""))
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    /// Earliest time that a retry is scheduled to run, if any are waiting
+    pub async fn get_next_scheduled(
+        db: &DatabaseConnection,
+        max_retries: u32,
+    ) -> Result<Option<Timestamp>, DbErr> {
+        Ok(subscription_queue::Entity::find()
+            .select_only()
+            .column(subscription_queue::Column::ScheduledFor)
+            .left_join(subscription_queue_result::Entity)
+            .filter(subscription_queue_result::Column::Timestamp.is_null())
+            .filter(subscription_queue::Column::ScheduledFor.is_not_null())
+            .filter(subscription_queue::Column::RetryCount.lt(max_retries))
+            .order_by_asc(subscription_queue::Column::ScheduledFor)
+            .into_tuple::<Option<JiffTimestampMilliseconds>>()
+            .one(db)
+            .await?
+            .flatten()
+            .map(|scheduled_for| scheduled_for.0))
+    }
+
+    /// Actions that have been retried `max_retries` times are left for [`SubscriptionQueue::get_dead_letters`]
+    pub async fn get_pending_actions(
+        db: &DatabaseConnection,
+        max_retries: u32,
+    ) -> Result<Vec<SubscriptionQueueItem>, DbErr> {
+        let query = subscription_queue::Entity::find()
+            .left_join(subscription_queue_result::Entity)
+            .filter(subscription_queue_result::Column::Timestamp.is_null())
+            .filter(subscription_queue::Column::RetryCount.lt(max_retries))
+            .filter(
+                Condition::any()
+                    .add(subscription_queue::Column::ScheduledFor.is_null())
+                    .add(
+                        subscription_queue::Column::ScheduledFor
+                            .lte(JiffTimestampMilliseconds(Timestamp::now())),
+                    ),
+            )
+            // Within the same kind of action, channels with a higher priority go first
+            .left_join(known_channels::Entity)
+            .order_by_asc(subscription_queue::Column::Priority)
+            .order_by_asc(known_channels::Column::Priority)
+            .order_by_asc(subscription_queue::Column::Id)
+            .find_also_linked(SubscriptionQueueToActiveSubscriptions);
+
+        #[cfg(debug_assertions)]
+        explain_query(db, &query).await;
+
+        Ok(query
+            .all(db) // TODO: paginate?
+            .await?
+            .into_iter()
+            .map(|(queue_item, active_subscription)| SubscriptionQueueItem {
+                queue_item,
+                active_subscription,
+                max_retries,
+                db: db.clone(),
+            })
+            .collect())
+    }
+
+    /// Most recently failed actions and their results, newest first
+    pub async fn get_failed_actions(
+        db: &DatabaseConnection,
+        limit: u64,
+    ) -> Result<Vec<(subscription_queue::Model, subscription_queue_result::Model)>, DbErr> {
+        let actions = subscription_queue::Entity::find()
+            .find_also_related(subscription_queue_result::Entity)
+            .filter(subscription_queue_result::Column::Error.is_not_null())
+            .filter(subscription_queue_result::Column::Skipped.eq(false))
+            .order_by_desc(subscription_queue_result::Column::Timestamp)
+            .limit(limit)
+            .all(db)
+            .await?;
+
+        Ok(actions
+            .into_iter()
+            .filter_map(|(action, result)| Some((action, result?)))
+            .collect())
+    }
+
+    /// Actions that will not be retried again, since they have failed `max_retries` times
+    pub async fn get_dead_letters(
+        db: &DatabaseConnection,
+        max_retries: u32,
+    ) -> Result<Vec<subscription_queue::Model>, DbErr> {
+        subscription_queue::Entity::find()
+            .left_join(subscription_queue_result::Entity)
+            .filter(subscription_queue_result::Column::Timestamp.is_null())
+            .filter(subscription_queue::Column::RetryCount.gte(max_retries))
+            .order_by_desc(subscription_queue::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// The action has not been processed and will not be retried again
+    pub async fn is_dead_letter(
+        db: &DatabaseConnection,
+        dead_letter_id: i64,
+        max_retries: u32,
+    ) -> Result<bool, DbErr> {
+        Ok(subscription_queue::Entity::find()
+            .left_join(subscription_queue_result::Entity)
+            .filter(subscription_queue::Column::Id.eq(dead_letter_id))
+            .filter(subscription_queue_result::Column::Timestamp.is_null())
+            .filter(subscription_queue::Column::RetryCount.gte(max_retries))
+            .count(db)
+            .await?
+            > 0)
+    }
+
+    /// Queue a dead letter again as a fresh action, and mark the dead letter as skipped so that
+    /// it is no longer listed
+    ///
+    /// Any unprocessed action is accepted, so check [`SubscriptionQueue::is_dead_letter`] first.
+    /// Returns [`DbErr::RecordNotFound`] if there is no unprocessed action with the id
+    pub async fn requeue_dead_letter(
+        db: &DatabaseConnection,
+        notify: &Notify,
+        dead_letter_id: i64,
+    ) -> Result<(), DbErr> {
+        let transaction = db.begin().await?;
+
+        let dead_letter = subscription_queue::Entity::find()
+            .left_join(subscription_queue_result::Entity)
+            .filter(subscription_queue::Column::Id.eq(dead_letter_id))
+            .filter(subscription_queue_result::Column::Timestamp.is_null())
+            .one(&transaction)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("dead letter {dead_letter_id}")))?;
+
+        let requeued = subscription_queue::Entity::insert(subscription_queue::ActiveModel {
+            id: ActiveValue::NotSet,
+            channel_id: ActiveValue::Set(dead_letter.channel_id),
+            action: ActiveValue::Set(dead_letter.action),
+            timestamp: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+            secret: ActiveValue::Set(dead_letter.secret),
+            scheduled_for: ActiveValue::Set(None),
+            priority: ActiveValue::Set(dead_letter.priority),
+            source: ActiveValue::Set(SubscriptionSource::AdminManual),
+            retry_count: ActiveValue::Set(0),
+            sent_at: ActiveValue::Set(None),
+            confirmed_at: ActiveValue::Set(None),
+        })
+        .exec(&transaction)
+        .await?;
+
+        subscription_queue_result::Entity::insert(
+            subscription_queue_result::Model {
+                queue_id: dead_letter.id,
+                error: Some(format!("requeued as {}", requeued.last_insert_id)),
+                timestamp: JiffTimestampMilliseconds(Timestamp::now()),
+                skipped: true,
+            }
+            .into_active_model(),
+        )
+        .exec(&transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        tracing::trace!("notifying subscription queue");
+        notify.notify_one();
+
+        Ok(())
+    }
+}
+
+/// Requests sent longer ago than this are no longer expected to be verified by the hub
+const VERIFICATION_WINDOW: SignedDuration = SignedDuration::from_hours(1);
+
+/// Message of the [`DbErr::Custom`] returned when the subscription queue is full
+const QUEUE_FULL: &str = "queue full";
+
+/// Upper bound of the delay before the first retry, doubling with each following failure
+const SUBSCRIPTION_RETRY_DELAY: SignedDuration = SignedDuration::from_mins(1);
+/// Retries are never delayed by more than this, no matter how many times the action has failed
+const SUBSCRIPTION_RETRY_DELAY_CAP: SignedDuration = SignedDuration::from_hours(1);
+
+/// Exponential backoff with full jitter, picking a random delay up to the exponential delay so
+/// that actions which failed together, such as when the hub rate limits a burst of requests, are
+/// not all retried together
+fn subscription_retry_delay(retry_count: i32) -> SignedDuration {
+    let max_delay = SUBSCRIPTION_RETRY_DELAY
+        .checked_mul(2i32.saturating_pow(retry_count.unsigned_abs()))
+        .map_or(SUBSCRIPTION_RETRY_DELAY_CAP, |delay| {
+            delay.min(SUBSCRIPTION_RETRY_DELAY_CAP)
+        });
+
+    SignedDuration::from_millis(rand::random_range(0..=max_delay.as_millis() as i64))
+}
+
+pub struct SubscriptionQueueItem {
+    queue_item: subscription_queue::Model,
+    active_subscription: Option<active_subscriptions::Model>,
+    max_retries: u32,
+    db: DatabaseConnection,
+}
+
+impl SubscriptionQueueItem {
+    pub fn action(&self) -> SubscriptionAction {
+        self.queue_item.action
+    }
+
+    pub fn channel_id(&self) -> &str {
+        &self.queue_item.channel_id
+    }
+
+    pub fn has_active_subscription(&self) -> bool {
+        self.active_subscription.is_some()
+    }
+
+    /// Run `function` on the queue item and store its result
+    ///
+    /// If `timeout` elapses before `function` completes, it is cancelled and a timeout error is stored
+    ///
+    /// Returns the error if the action failed for the last time and was left as a dead letter
+    pub async fn process<F, E>(
+        self,
+        timeout: Option<Duration>,
+        function: F,
+    ) -> Result<Option<String>, DbErr>
+    where
+        F: AsyncFnOnce(
+                &subscription_queue::Model,
+                Option<&active_subscriptions::Model>,
+            ) -> Result<(), E>
+            + Send
+            + Sync,
+        E: Error + Send + Sync,
+    {
+        let future = function(&self.queue_item, self.active_subscription.as_ref());
+
+        let result = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, future).await {
+                Ok(result) => result.map_err(|error| error.to_string()),
+                Err(_) => Err(format!(
+                    "timed out after {}s processing {:?} action",
+                    timeout.as_secs_f64(),
+                    self.queue_item.action
+                )),
+            },
+            None => future.await.map_err(|error| error.to_string()),
+        };
+
+        let model = match result {
+            Ok(()) => subscription_queue_result::Model {
+                queue_id: self.queue_item.id,
+                error: None,
+                timestamp: JiffTimestampMilliseconds(Timestamp::now()),
+                skipped: false,
+            },
+            Err(error) => {
+                tracing::error!(%error, "failed to process subscription queue item");
+
+                subscription_queue_result::Model {
+                    queue_id: self.queue_item.id,
+                    error: Some(error),
+                    timestamp: JiffTimestampMilliseconds(Timestamp::now()),
+                    skipped: false,
+                }
+            }
+        };
+
+        let transaction = self.db.begin().await?;
+
+        let dead_letter = match model.error {
+            Some(error) if self.is_last_attempt() => {
+                self.give_up(&transaction).await?;
+                Some(error)
+            }
+            Some(_) => {
+                subscription_queue_result::Entity::insert(model.into_active_model())
+                    .exec(&transaction)
+                    .await?;
+                self.schedule_retry(&transaction).await?;
+                None
+            }
+            None => {
+                subscription_queue_result::Entity::insert(model.into_active_model())
+                    .exec(&transaction)
+                    .await?;
+                None
+            }
+        };
+
+        transaction.commit().await?;
+
+        Ok(dead_letter)
+    }
+
+    /// Store a result for an action that was not attempted, which is not retried
+    pub async fn skip(self, reason: &str) -> Result<(), DbErr> {
+        tracing::warn!(queue_item = ?self.queue_item, reason, "skipping subscription queue item");
+
+        subscription_queue_result::Entity::insert(
+            subscription_queue_result::Model {
+                queue_id: self.queue_item.id,
+                error: Some(reason.to_owned()),
+                timestamp: JiffTimestampMilliseconds(Timestamp::now()),
+                skipped: true,
+            }
+            .into_active_model(),
+        )
+        .exec(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Failing again would bring the action to `max_retries`, so it will not be retried
+    fn is_last_attempt(&self) -> bool {
+        u32::try_from(self.queue_item.retry_count + 1)
+            .is_ok_and(|retry_count| retry_count >= self.max_retries)
+    }
+
+    /// Leave the action unprocessed with its retry count at the limit, so that it shows up as a
+    /// dead letter instead of being processed again
+    async fn give_up(&self, transaction: &DatabaseTransaction) -> Result<(), DbErr> {
+        let retry_count = self.queue_item.retry_count + 1;
+        tracing::warn!(queue_item = ?self.queue_item, retry_count, "giving up on subscription queue item");
+
+        subscription_queue::Entity::update_many()
+            .col_expr(
+                subscription_queue::Column::RetryCount,
+                Expr::value(retry_count),
+            )
+            .filter(subscription_queue::Column::Id.eq(self.queue_item.id))
+            .exec(transaction)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Queue the action again, backing off exponentially with jitter after each consecutive failure
+    ///
+    /// Each attempt gets its own row so that every failure keeps its result
+    async fn schedule_retry(&self, transaction: &DatabaseTransaction) -> Result<(), DbErr> {
+        let retry_count = self.queue_item.retry_count + 1;
+
+        let scheduled_for =
+            Timestamp::now() + subscription_retry_delay(self.queue_item.retry_count);
+        tracing::debug!(queue_item = ?self.queue_item, %scheduled_for, "scheduling retry");
+
+        subscription_queue::Entity::insert(subscription_queue::ActiveModel {
+            id: ActiveValue::NotSet,
+            channel_id: ActiveValue::Set(self.queue_item.channel_id.clone()),
+            action: ActiveValue::Set(self.queue_item.action),
+            timestamp: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+            secret: ActiveValue::Set(self.queue_item.secret.clone()),
+            scheduled_for: ActiveValue::Set(Some(JiffTimestampMilliseconds(scheduled_for))),
+            priority: ActiveValue::Set(self.queue_item.priority),
+            source: ActiveValue::Set(SubscriptionSource::VerificationFailed),
+            retry_count: ActiveValue::Set(retry_count),
+            sent_at: ActiveValue::Set(None),
+            confirmed_at: ActiveValue::Set(None),
+        })
+        .exec(transaction)
+        .await?;
+
+        Ok(())
+    }
+}
+
+pub struct ChannelSubscriptionStatus {
+    pub channel_id: String,
+    pub channel_name: String,
+    pub last_video_at: Option<Timestamp>,
+    /// [`None`] if there is no active subscription
+    pub expiration: Option<Timestamp>,
+    pub pending_actions_count: i64,
+}
+
+/// Shorts ratio assumed for channels without any detected videos
+const SHORTS_RATIO_PRIOR: f64 = 0.5;
+/// Weight of each new video in the running average of the shorts ratio
+const SHORTS_RATIO_WEIGHT: f64 = 0.2;
+/// Profile pictures synced from youtube are kept for at least this long before being replaced
+const PROFILE_PICTURE_MAX_AGE: SignedDuration = SignedDuration::from_hours(30 * 24);
+
+pub struct KnownChannels;
+
+impl KnownChannels {
+    pub async fn count(db: &DatabaseConnection) -> Result<u64, DbErr> {
+        known_channels::Entity::find().count(db).await
+    }
+
+    pub async fn add_channels(
+        db: &DatabaseConnection,
+        channels: impl IntoIterator<Item = known_channels::Model>,
+    ) -> Result<(), DbErr> {
+        // Profile pictures are only replaced once they are stale, or if they were never dated
+        let stale_picture = Expr::cust_with_values(
+            "\"known_channels\".\"profile_picture_updated_at\" IS NULL OR \"known_channels\".\"profile_picture_updated_at\" < ?",
+            [JiffTimestampMilliseconds(
+                Timestamp::now() - PROFILE_PICTURE_MAX_AGE,
+            )],
+        );
+
+        known_channels::Entity::insert_many(
+            channels.into_iter().map(IntoActiveModel::into_active_model),
         )
         .on_conflict(
+            // Only update the metadata sourced from youtube, leaving local settings alone
             OnConflict::column(known_channels::Column::ChannelId)
-                .update_columns(known_channels::Column::iter())
+                .update_columns([
+                    known_channels::Column::ChannelName,
+                    known_channels::Column::MetadataUpdatedAt,
+                ])
+                .value(
+                    known_channels::Column::ChannelProfilePicture,
+                    Expr::case(
+                        stale_picture.clone(),
+                        Expr::cust("\"excluded\".\"channel_profile_picture\""),
+                    )
+                    .finally(Expr::col((
+                        known_channels::Entity,
+                        known_channels::Column::ChannelProfilePicture,
+                    ))),
+                )
+                .value(
+                    known_channels::Column::ProfilePictureUpdatedAt,
+                    Expr::case(
+                        stale_picture,
+                        Expr::cust("\"excluded\".\"profile_picture_updated_at\""),
+                    )
+                    .finally(Expr::col((
+                        known_channels::Entity,
+                        known_channels::Column::ProfilePictureUpdatedAt,
+                    ))),
+                )
+                // Keep the previous handle if it could not be fetched
+                .value(
+                    known_channels::Column::Handle,
+                    Expr::cust("COALESCE(\"excluded\".\"handle\", \"known_channels\".\"handle\")"),
+                )
+                .value(
+                    known_channels::Column::SubscriptionCount,
+                    Expr::cust("COALESCE(\"excluded\".\"subscription_count\", \"known_channels\".\"subscription_count\")"),
+                )
+                .value(
+                    known_channels::Column::SubscriberCount,
+                    Expr::cust("COALESCE(\"excluded\".\"subscriber_count\", \"known_channels\".\"subscriber_count\")"),
+                )
                 .to_owned(),
         )
         .exec(db)
@@ -221,6 +1535,822 @@ impl KnownChannels {
 
         Ok(())
     }
+
+    /// Update the metadata sourced from youtube, leaving out the fields that are [`None`]
+    pub async fn update_channel_metadata(
+        db: &DatabaseConnection,
+        channel_id: &str,
+        name: Option<&str>,
+        picture: Option<&str>,
+    ) -> Result<(), DbErr> {
+        let mut update = known_channels::Entity::update_many()
+            .col_expr(
+                known_channels::Column::MetadataUpdatedAt,
+                Expr::value(JiffTimestampMilliseconds(Timestamp::now())),
+            )
+            .filter(known_channels::Column::ChannelId.eq(channel_id));
+
+        if let Some(name) = name {
+            update = update.col_expr(known_channels::Column::ChannelName, Expr::value(name));
+        }
+
+        if let Some(picture) = picture {
+            update = update
+                .col_expr(
+                    known_channels::Column::ChannelProfilePicture,
+                    Expr::value(picture),
+                )
+                .col_expr(
+                    known_channels::Column::ProfilePictureUpdatedAt,
+                    Expr::value(JiffTimestampMilliseconds(Timestamp::now())),
+                );
+        }
+
+        update.exec(db).await?;
+
+        Ok(())
+    }
+
+    /// Channels whose metadata has not been updated since `before`, oldest first
+    pub async fn get_stale_metadata_channel_ids(
+        db: &DatabaseConnection,
+        before: Timestamp,
+    ) -> Result<Vec<String>, DbErr> {
+        known_channels::Entity::find()
+            .select_only()
+            .column(known_channels::Column::ChannelId)
+            .filter(
+                Condition::any()
+                    .add(known_channels::Column::MetadataUpdatedAt.is_null())
+                    .add(
+                        known_channels::Column::MetadataUpdatedAt
+                            .lt(JiffTimestampMilliseconds(before)),
+                    ),
+            )
+            .order_by_asc(known_channels::Column::MetadataUpdatedAt)
+            .into_tuple::<String>()
+            .all(db)
+            .await
+    }
+
+    pub async fn get_channel(
+        db: &DatabaseConnection,
+        channel_id: &str,
+    ) -> Result<Option<known_channels::Model>, DbErr> {
+        known_channels::Entity::find_by_id(channel_id).one(db).await
+    }
+
+    /// Subscription details of every known channel, ordered by name
+    /// Dead letters are not counted as pending actions, since they will never be processed
+    /// unless they are requeued
+    pub async fn get_subscription_statuses(
+        db: &DatabaseConnection,
+        max_retries: u32,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<ChannelSubscriptionStatus>, DbErr> {
+        Ok(known_channels::Entity::find()
+            .select_only()
+            .column(known_channels::Column::ChannelId)
+            .column(known_channels::Column::ChannelName)
+            .column(known_channels::Column::LastVideoAt)
+            .column(active_subscriptions::Column::Expiration)
+            .column_as(
+                Expr::cust_with_values(
+                    "(SELECT COUNT(*) FROM \"subscription_queue\" \
+                    LEFT JOIN \"subscription_queue_result\" \
+                    ON \"subscription_queue_result\".\"queue_id\" = \"subscription_queue\".\"id\" \
+                    WHERE \"subscription_queue\".\"channel_id\" = \"known_channels\".\"channel_id\" \
+                    AND \"subscription_queue_result\".\"queue_id\" IS NULL \
+                    AND \"subscription_queue\".\"retry_count\" < ?)",
+                    [max_retries],
+                ),
+                "pending_actions_count",
+            )
+            .left_join(active_subscriptions::Entity)
+            .order_by_asc(known_channels::Column::ChannelName)
+            .limit(limit)
+            .offset(offset)
+            .into_tuple::<(
+                String,
+                String,
+                Option<JiffTimestampMilliseconds>,
+                Option<JiffTimestampMilliseconds>,
+                i64,
+            )>()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(
+                |(channel_id, channel_name, last_video_at, expiration, pending_actions_count)| {
+                    ChannelSubscriptionStatus {
+                        channel_id,
+                        channel_name,
+                        last_video_at: last_video_at.map(|timestamp| timestamp.0),
+                        expiration: expiration.map(|timestamp| timestamp.0),
+                        pending_actions_count,
+                    }
+                },
+            )
+            .collect())
+    }
+
+    pub async fn get_all_channel_ids(db: &DatabaseConnection) -> Result<Vec<String>, DbErr> {
+        known_channels::Entity::find()
+            .select_only()
+            .column(known_channels::Column::ChannelId)
+            .into_tuple::<String>()
+            .all(db)
+            .await
+    }
+
+    /// Update locally managed channel settings, returning `false` if the channel does not exist
+    pub async fn update_settings(
+        db: &DatabaseConnection,
+        channel_id: &str,
+        is_shorts_channel: Option<bool>,
+        priority: Option<i32>,
+    ) -> Result<bool, DbErr> {
+        if is_shorts_channel.is_none() && priority.is_none() {
+            return Ok(known_channels::Entity::find_by_id(channel_id)
+                .one(db)
+                .await?
+                .is_some());
+        }
+
+        let mut update = known_channels::Entity::update_many()
+            .filter(known_channels::Column::ChannelId.eq(channel_id));
+        if let Some(is_shorts_channel) = is_shorts_channel {
+            update = update.col_expr(
+                known_channels::Column::IsShortsChannel,
+                Expr::value(is_shorts_channel),
+            );
+        }
+        if let Some(priority) = priority {
+            update = update.col_expr(known_channels::Column::Priority, Expr::value(priority));
+        }
+
+        let result = update.exec(db).await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Returns `false` if the channel does not exist
+    pub async fn set_notifications_paused(
+        db: &DatabaseConnection,
+        channel_id: &str,
+        notifications_paused: bool,
+    ) -> Result<bool, DbErr> {
+        let result = known_channels::Entity::update_many()
+            .col_expr(
+                known_channels::Column::NotificationsPaused,
+                Expr::value(notifications_paused),
+            )
+            .filter(known_channels::Column::ChannelId.eq(channel_id))
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Unknown channels are never paused
+    pub async fn is_notifications_paused(
+        db: &DatabaseConnection,
+        channel_id: &str,
+    ) -> Result<bool, DbErr> {
+        Ok(known_channels::Entity::find_by_id(channel_id)
+            .select_only()
+            .column(known_channels::Column::NotificationsPaused)
+            .into_tuple::<bool>()
+            .one(db)
+            .await?
+            .unwrap_or(false))
+    }
+
+    /// Record that the channel has posted a video, if it is newer than the last known video
+    pub async fn update_last_video_at(
+        db: &DatabaseConnection,
+        channel_id: &str,
+        last_video_at: Timestamp,
+    ) -> Result<(), DbErr> {
+        known_channels::Entity::update_many()
+            .col_expr(
+                known_channels::Column::LastVideoAt,
+                Expr::value(JiffTimestampMilliseconds(last_video_at)),
+            )
+            .filter(known_channels::Column::ChannelId.eq(channel_id))
+            .filter(known_channels::Column::LastVideoAt.is_null().or(
+                known_channels::Column::LastVideoAt.lt(JiffTimestampMilliseconds(last_video_at)),
+            ))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Fold the verified type of a new video into the running average of how many of the
+    /// channel's videos are shorts
+    pub async fn update_shorts_ratio(
+        db: &DatabaseConnection,
+        channel_id: &str,
+        is_short: bool,
+    ) -> Result<(), DbErr> {
+        known_channels::Entity::update_many()
+            .col_expr(
+                known_channels::Column::ShortsRatio,
+                Expr::col(known_channels::Column::ShortsRatio)
+                    .if_null(SHORTS_RATIO_PRIOR)
+                    .mul(1.0 - SHORTS_RATIO_WEIGHT)
+                    .add(if is_short { SHORTS_RATIO_WEIGHT } else { 0.0 }),
+            )
+            .col_expr(
+                known_channels::Column::ShortsRatioSamples,
+                Expr::col(known_channels::Column::ShortsRatioSamples).add(1),
+            )
+            .filter(known_channels::Column::ChannelId.eq(channel_id))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Channels that have not posted a video since `before`
+    ///
+    /// Channels that have never posted a video since being added are not included
+    pub async fn get_inactive(
+        db: &DatabaseConnection,
+        before: Timestamp,
+    ) -> Result<Vec<known_channels::Model>, DbErr> {
+        known_channels::Entity::find()
+            .filter(known_channels::Column::LastVideoAt.lt(JiffTimestampMilliseconds(before)))
+            .order_by_asc(known_channels::Column::LastVideoAt)
+            .all(db)
+            .await
+    }
+
+    /// Channels that have not posted a video since `before` and are still subscribed to
+    pub async fn get_inactive_subscribed(
+        db: &DatabaseConnection,
+        before: Timestamp,
+    ) -> Result<Vec<known_channels::Model>, DbErr> {
+        known_channels::Entity::find()
+            .inner_join(active_subscriptions::Entity)
+            .filter(known_channels::Column::LastVideoAt.lt(JiffTimestampMilliseconds(before)))
+            .order_by_asc(known_channels::Column::LastVideoAt)
+            .all(db)
+            .await
+    }
+
+    /// Case-insensitive partial match on the channel name
+    pub async fn search(
+        db: &DatabaseConnection,
+        query: &str,
+        limit: u64,
+    ) -> Result<Vec<known_channels::Model>, DbErr> {
+        let escaped = query
+            .to_lowercase()
+            .replace('\\', r"\\")
+            .replace('%', r"\%")
+            .replace('_', r"\_");
+
+        known_channels::Entity::find()
+            .filter(
+                Expr::expr(Func::lower(Expr::col(known_channels::Column::ChannelName)))
+                    .like(LikeExpr::new(format!("%{escaped}%")).escape('\\')),
+            )
+            .order_by_asc(known_channels::Column::ChannelName)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+}
+
+pub struct ChannelGroups;
+
+impl ChannelGroups {
+    pub async fn get_all_with_members(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<(channel_groups::Model, Vec<channel_group_members::Model>)>, DbErr> {
+        channel_groups::Entity::find()
+            .order_by_asc(channel_groups::Column::Name)
+            .find_with_related(channel_group_members::Entity)
+            .all(db)
+            .await
+    }
+
+    pub async fn create(
+        db: &DatabaseConnection,
+        name: String,
+    ) -> Result<channel_groups::Model, DbErr> {
+        channel_groups::Entity::insert(channel_groups::ActiveModel {
+            id: ActiveValue::NotSet,
+            name: ActiveValue::Set(name),
+        })
+        .exec_with_returning(db)
+        .await
+    }
+
+    /// Replace the members of a group, returning `false` if the group does not exist
+    pub async fn set_members(
+        db: &DatabaseConnection,
+        group_id: i32,
+        channel_ids: impl IntoIterator<Item = String>,
+    ) -> Result<bool, DbErr> {
+        let transaction = db.begin().await?;
+
+        if channel_groups::Entity::find_by_id(group_id)
+            .one(&transaction)
+            .await?
+            .is_none()
+        {
+            return Ok(false);
+        }
+
+        channel_group_members::Entity::delete_many()
+            .filter(channel_group_members::Column::GroupId.eq(group_id))
+            .exec(&transaction)
+            .await?;
+
+        channel_group_members::Entity::insert_many(channel_ids.into_iter().map(|channel_id| {
+            channel_group_members::Model {
+                group_id,
+                channel_id,
+            }
+            .into_active_model()
+        }))
+        .on_empty_do_nothing()
+        .exec(&transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Remove a channel from a group, returning `false` if it was not a member
+    pub async fn remove_member(
+        db: &DatabaseConnection,
+        group_id: i32,
+        channel_id: String,
+    ) -> Result<bool, DbErr> {
+        let result = channel_group_members::Entity::delete_by_id((group_id, channel_id))
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// `None` if the group does not exist
+    pub async fn get_member_channel_ids(
+        db: &DatabaseConnection,
+        group_id: i32,
+    ) -> Result<Option<Vec<String>>, DbErr> {
+        if channel_groups::Entity::find_by_id(group_id)
+            .one(db)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        channel_group_members::Entity::find()
+            .select_only()
+            .column(channel_group_members::Column::ChannelId)
+            .filter(channel_group_members::Column::GroupId.eq(group_id))
+            .into_tuple::<String>()
+            .all(db)
+            .await
+            .map(Some)
+    }
+}
+
+pub struct AuditLog;
+
+impl AuditLog {
+    pub async fn record_audit_event(
+        db: &DatabaseConnection,
+        action: &str,
+        actor: &str,
+        target: Option<&str>,
+        details: Option<&str>,
+    ) -> Result<(), DbErr> {
+        audit_log::Entity::insert(audit_log::ActiveModel {
+            id: ActiveValue::NotSet,
+            action: ActiveValue::Set(action.to_owned()),
+            actor: ActiveValue::Set(actor.to_owned()),
+            target: ActiveValue::Set(target.map(ToOwned::to_owned)),
+            details: ActiveValue::Set(details.map(ToOwned::to_owned)),
+            timestamp: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+        })
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_recent(
+        db: &DatabaseConnection,
+        limit: u64,
+    ) -> Result<Vec<audit_log::Model>, DbErr> {
+        audit_log::Entity::find()
+            .order_by_desc(audit_log::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+}
+
+pub struct ShortsDetectionCache;
+
+impl ShortsDetectionCache {
+    pub async fn get(db: &DatabaseConnection, video_id: &str) -> Result<Option<bool>, DbErr> {
+        Ok(shorts_detection_cache::Entity::find_by_id(video_id)
+            .one(db)
+            .await?
+            .map(|cached| cached.is_short))
+    }
+
+    /// Every video that has been detected as a short
+    pub async fn get_shorts(db: &DatabaseConnection) -> Result<HashSet<String>, DbErr> {
+        Ok(shorts_detection_cache::Entity::find()
+            .select_only()
+            .column(shorts_detection_cache::Column::VideoId)
+            .filter(shorts_detection_cache::Column::IsShort.eq(true))
+            .into_tuple::<String>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect())
+    }
+
+    pub async fn record(
+        db: &DatabaseConnection,
+        video_id: &str,
+        is_short: bool,
+        detection_method: DetectionMethod,
+    ) -> Result<(), DbErr> {
+        shorts_detection_cache::Entity::insert(shorts_detection_cache::ActiveModel {
+            video_id: ActiveValue::Set(video_id.to_owned()),
+            is_short: ActiveValue::Set(is_short),
+            detection_method: ActiveValue::Set(detection_method),
+            detected_at: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+        })
+        .on_conflict(
+            OnConflict::column(shorts_detection_cache::Column::VideoId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Failed parses beyond this many are deleted, oldest first
+const MAX_FAILED_FEED_PARSES: i64 = 1000;
+/// Longer bodies are truncated before being stored
+const MAX_FAILED_FEED_PARSE_BODY_BYTES: usize = 4 * 1024;
+
+pub struct FailedFeedParses;
+
+impl FailedFeedParses {
+    /// Bodies come from unauthenticated requests, so only the start of the body is stored and
+    /// older parses are deleted beyond [`MAX_FAILED_FEED_PARSES`]
+    pub async fn record(db: &DatabaseConnection, body: &str, error: &str) -> Result<(), DbErr> {
+        let body = &body[..body.floor_char_boundary(MAX_FAILED_FEED_PARSE_BODY_BYTES)];
+
+        let transaction = db.begin().await?;
+
+        let inserted = failed_feed_parses::Entity::insert(failed_feed_parses::ActiveModel {
+            id: ActiveValue::NotSet,
+            body: ActiveValue::Set(body.to_owned()),
+            error: ActiveValue::Set(error.to_owned()),
+            timestamp: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+        })
+        .exec(&transaction)
+        .await?;
+
+        failed_feed_parses::Entity::delete_many()
+            .filter(
+                failed_feed_parses::Column::Id
+                    .lte(inserted.last_insert_id - MAX_FAILED_FEED_PARSES),
+            )
+            .exec(&transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Most recently failed parses, newest first
+    pub async fn get_recent(
+        db: &DatabaseConnection,
+        limit: u64,
+    ) -> Result<Vec<failed_feed_parses::Model>, DbErr> {
+        failed_feed_parses::Entity::find()
+            .order_by_desc(failed_feed_parses::Column::Timestamp)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+}
+
+pub struct VideoBlacklist;
+
+impl VideoBlacklist {
+    /// Block a video, replacing the reason if it was already blocked
+    pub async fn add(
+        db: &DatabaseConnection,
+        video_id: &str,
+        reason: &str,
+        actor: &str,
+    ) -> Result<(), DbErr> {
+        video_blacklist::Entity::insert(video_blacklist::ActiveModel {
+            video_id: ActiveValue::Set(video_id.to_owned()),
+            reason: ActiveValue::Set(reason.to_owned()),
+            actor: ActiveValue::Set(actor.to_owned()),
+            timestamp: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+        })
+        .on_conflict(
+            OnConflict::column(video_blacklist::Column::VideoId)
+                .update_columns([
+                    video_blacklist::Column::Reason,
+                    video_blacklist::Column::Actor,
+                    video_blacklist::Column::Timestamp,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Unblock a video, returning `false` if it was not blocked
+    pub async fn remove(db: &DatabaseConnection, video_id: &str) -> Result<bool, DbErr> {
+        let result = video_blacklist::Entity::delete_by_id(video_id)
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    pub async fn is_blacklisted(db: &DatabaseConnection, video_id: &str) -> Result<bool, DbErr> {
+        Ok(video_blacklist::Entity::find_by_id(video_id)
+            .count(db)
+            .await?
+            > 0)
+    }
+
+    /// Most recently blocked first
+    pub async fn get_page(
+        db: &DatabaseConnection,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<video_blacklist::Model>, DbErr> {
+        video_blacklist::Entity::find()
+            .order_by_desc(video_blacklist::Column::Timestamp)
+            .order_by_asc(video_blacklist::Column::VideoId)
+            .limit(limit)
+            .offset(offset)
+            .all(db)
+            .await
+    }
+}
+
+pub struct MaintenanceWindows;
+
+impl MaintenanceWindows {
+    pub async fn create(
+        db: &DatabaseConnection,
+        starts_at: Timestamp,
+        ends_at: Timestamp,
+        reason: String,
+    ) -> Result<maintenance_windows::Model, DbErr> {
+        maintenance_windows::Entity::insert(maintenance_windows::ActiveModel {
+            id: ActiveValue::NotSet,
+            starts_at: ActiveValue::Set(JiffTimestampMilliseconds(starts_at)),
+            ends_at: ActiveValue::Set(JiffTimestampMilliseconds(ends_at)),
+            reason: ActiveValue::Set(reason),
+        })
+        .exec_with_returning(db)
+        .await
+    }
+
+    /// Returns [`None`] if the window does not exist
+    pub async fn update(
+        db: &DatabaseConnection,
+        id: i64,
+        starts_at: Timestamp,
+        ends_at: Timestamp,
+        reason: String,
+    ) -> Result<Option<maintenance_windows::Model>, DbErr> {
+        let result = maintenance_windows::Entity::update(maintenance_windows::ActiveModel {
+            id: ActiveValue::Unchanged(id),
+            starts_at: ActiveValue::Set(JiffTimestampMilliseconds(starts_at)),
+            ends_at: ActiveValue::Set(JiffTimestampMilliseconds(ends_at)),
+            reason: ActiveValue::Set(reason),
+        })
+        .exec(db)
+        .await;
+
+        match result {
+            Ok(window) => Ok(Some(window)),
+            Err(DbErr::RecordNotUpdated) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns `false` if the window does not exist
+    pub async fn delete(db: &DatabaseConnection, id: i64) -> Result<bool, DbErr> {
+        let result = maintenance_windows::Entity::delete_by_id(id)
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Windows that have not ended yet, soonest first
+    pub async fn get_upcoming(
+        db: &DatabaseConnection,
+        now: Timestamp,
+    ) -> Result<Vec<maintenance_windows::Model>, DbErr> {
+        maintenance_windows::Entity::find()
+            .filter(maintenance_windows::Column::EndsAt.gt(JiffTimestampMilliseconds(now)))
+            .order_by_asc(maintenance_windows::Column::StartsAt)
+            .all(db)
+            .await
+    }
+
+    /// When the latest ending window that is in progress ends, [`None`] if there is no window in
+    /// progress
+    pub async fn get_active_end(
+        db: &DatabaseConnection,
+        now: Timestamp,
+    ) -> Result<Option<Timestamp>, DbErr> {
+        Ok(maintenance_windows::Entity::find()
+            .select_only()
+            .column(maintenance_windows::Column::EndsAt)
+            .filter(maintenance_windows::Column::StartsAt.lte(JiffTimestampMilliseconds(now)))
+            .filter(maintenance_windows::Column::EndsAt.gt(JiffTimestampMilliseconds(now)))
+            .order_by_desc(maintenance_windows::Column::EndsAt)
+            .into_tuple::<JiffTimestampMilliseconds>()
+            .one(db)
+            .await?
+            .map(|ends_at| ends_at.0))
+    }
+}
+
+pub struct SubscriptionRefreshState;
+
+impl SubscriptionRefreshState {
+    /// The table only ever holds a single row with this id
+    const ID: i32 = 0;
+
+    pub async fn get_next_wakeup(db: &DatabaseConnection) -> Result<Option<Timestamp>, DbErr> {
+        Ok(subscription_refresh_state::Entity::find_by_id(Self::ID)
+            .one(db)
+            .await?
+            .map(|state| state.next_wakeup_at.0))
+    }
+
+    pub async fn set_next_wakeup(
+        db: &DatabaseConnection,
+        next_wakeup_at: Timestamp,
+    ) -> Result<(), DbErr> {
+        subscription_refresh_state::Entity::insert(subscription_refresh_state::ActiveModel {
+            id: ActiveValue::Set(Self::ID),
+            next_wakeup_at: ActiveValue::Set(JiffTimestampMilliseconds(next_wakeup_at)),
+        })
+        .on_conflict(
+            OnConflict::column(subscription_refresh_state::Column::Id)
+                .update_column(subscription_refresh_state::Column::NextWakeupAt)
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// When each periodic actor last finished a run, so that restarts do not postpone the next one
+pub struct ActorRuns;
+
+impl ActorRuns {
+    pub async fn get_last_run(
+        db: &DatabaseConnection,
+        name: &str,
+    ) -> Result<Option<Timestamp>, DbErr> {
+        Ok(actor_runs::Entity::find_by_id(name)
+            .one(db)
+            .await?
+            .map(|run| run.last_run_at.0))
+    }
+
+    pub async fn record_run(db: &DatabaseConnection, name: &str) -> Result<(), DbErr> {
+        actor_runs::Entity::insert(actor_runs::ActiveModel {
+            name: ActiveValue::Set(name.to_owned()),
+            last_run_at: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+        })
+        .on_conflict(
+            OnConflict::column(actor_runs::Column::Name)
+                .update_column(actor_runs::Column::LastRunAt)
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+pub struct SubscriptionSyncRuns;
+
+impl SubscriptionSyncRuns {
+    pub async fn record(
+        db: &DatabaseConnection,
+        channels_added: i32,
+        channels_removed: i32,
+        api_pages: i32,
+        duration: Duration,
+        etag_hit: bool,
+    ) -> Result<(), DbErr> {
+        subscription_sync_runs::Entity::insert(subscription_sync_runs::ActiveModel {
+            id: ActiveValue::NotSet,
+            timestamp: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+            channels_added: ActiveValue::Set(channels_added),
+            channels_removed: ActiveValue::Set(channels_removed),
+            api_pages: ActiveValue::Set(api_pages),
+            duration_ms: ActiveValue::Set(duration.as_millis().try_into().unwrap_or(i64::MAX)),
+            etag_hit: ActiveValue::Set(etag_hit),
+        })
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_recent(
+        db: &DatabaseConnection,
+        limit: u64,
+    ) -> Result<Vec<subscription_sync_runs::Model>, DbErr> {
+        subscription_sync_runs::Entity::find()
+            .order_by_desc(subscription_sync_runs::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+}
+
+pub struct YoutubeApiCalls;
+
+impl YoutubeApiCalls {
+    /// `status_code` is [`None`] if no response was received
+    pub async fn record(
+        db: &DatabaseConnection,
+        endpoint: &str,
+        video_id: Option<&str>,
+        channel_id: Option<&str>,
+        quota_cost: i32,
+        status_code: Option<u16>,
+    ) -> Result<(), DbErr> {
+        youtube_api_calls::Entity::insert(youtube_api_calls::ActiveModel {
+            id: ActiveValue::NotSet,
+            endpoint: ActiveValue::Set(endpoint.to_owned()),
+            video_id: ActiveValue::Set(video_id.map(str::to_owned)),
+            channel_id: ActiveValue::Set(channel_id.map(str::to_owned)),
+            quota_cost: ActiveValue::Set(quota_cost),
+            status_code: ActiveValue::Set(status_code.map(i32::from)),
+            timestamp: ActiveValue::Set(JiffTimestampMilliseconds(Timestamp::now())),
+        })
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Number of calls and quota used by each endpoint between `since` and `until`, ordered by endpoint
+    pub async fn get_usage(
+        db: &DatabaseConnection,
+        since: Timestamp,
+        until: Timestamp,
+    ) -> Result<Vec<(String, i64, i64)>, DbErr> {
+        youtube_api_calls::Entity::find()
+            .select_only()
+            .column(youtube_api_calls::Column::Endpoint)
+            .column_as(youtube_api_calls::Column::Id.count(), "calls")
+            .column_as(youtube_api_calls::Column::QuotaCost.sum(), "quota_cost")
+            .filter(youtube_api_calls::Column::Timestamp.gte(JiffTimestampMilliseconds(since)))
+            .filter(youtube_api_calls::Column::Timestamp.lt(JiffTimestampMilliseconds(until)))
+            .group_by(youtube_api_calls::Column::Endpoint)
+            .order_by_asc(youtube_api_calls::Column::Endpoint)
+            .into_tuple()
+            .all(db)
+            .await
+    }
 }
 
 pub struct OAuth;
@@ -1,38 +1,242 @@
+use std::{error::Error, fmt::Display};
+
 use jiff::Timestamp;
 use monostate::MustBe;
+use quick_xml::{
+    DeError, NsReader,
+    events::Event,
+    name::{Namespace, ResolveResult},
+};
 use serde::{Deserialize, Serialize};
 
 #[cfg(test)]
 mod test {
-    use crate::feed::Feed;
+    use crate::feed::{EntryValidationError, Feed};
 
     #[test]
     fn parse_sample_file() {
         let sample_video = include_str!("../test_data/sample_video.xml");
 
-        dbg!(quick_xml::de::from_str::<Feed>(sample_video).unwrap());
+        dbg!(Feed::from_xml(sample_video).unwrap());
+    }
+
+    #[test]
+    fn parse_other_namespace_prefix() {
+        let sample_video = include_str!("../test_data/sample_video.xml")
+            .replace("xmlns:yt=", "xmlns:youtube=")
+            .replace("<yt:", "<youtube:")
+            .replace("</yt:", "</youtube:");
+
+        let feed = Feed::from_xml(&sample_video).unwrap();
+
+        assert_eq!(feed.entry.video_id, "29w5v9DRHY0");
+        assert_eq!(feed.entry.channel_id, "UCHtv-7yDeac7OSfPJA_a6aA");
+    }
+
+    #[test]
+    fn parse_media_group_short_hint() {
+        let sample_video = include_str!("../test_data/sample_video.xml");
+        assert_eq!(
+            Feed::from_xml(sample_video).unwrap().entry.is_short_hint(),
+            None
+        );
+
+        let with_thumbnail = |width: u32, height: u32| {
+            sample_video
+                .replace(
+                    "xmlns=\"http://www.w3.org/2005/Atom\"",
+                    "xmlns=\"http://www.w3.org/2005/Atom\" xmlns:media=\"http://search.yahoo.com/mrss/\"",
+                )
+                .replace(
+                    "</entry>",
+                    &format!(
+                        r#"<media:group><media:thumbnail url="https://i.ytimg.com/vi/29w5v9DRHY0/hqdefault.jpg" width="{width}" height="{height}"/></media:group></entry>"#
+                    ),
+                )
+        };
+
+        let portrait = Feed::from_xml(&with_thumbnail(360, 480)).unwrap();
+        assert_eq!(portrait.entry.is_short_hint(), Some(true));
+
+        let landscape = Feed::from_xml(&with_thumbnail(480, 360)).unwrap();
+        assert_eq!(landscape.entry.is_short_hint(), None);
+    }
+
+    #[test]
+    fn validate_sample_file() {
+        let sample_video = include_str!("../test_data/sample_video.xml");
+        let mut feed = Feed::from_xml(sample_video).unwrap();
+
+        assert_eq!(feed.entry.validate(), Ok(()));
+
+        feed.entry.video_id.push('!');
+        assert_eq!(
+            feed.entry.validate(),
+            Err(EntryValidationError::InvalidVideoId)
+        );
     }
 }
 
+const YOUTUBE_NAMESPACE: &[u8] = b"http://www.youtube.com/xml/schemas/2015";
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Feed {
     #[serde(rename = "@xmlns")]
     _namespace: MustBe!("http://www.w3.org/2005/Atom"),
-    #[serde(rename = "@xmlns:yt")]
-    _namespace_yt: MustBe!("http://www.youtube.com/xml/schemas/2015"),
     pub entry: Entry,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl Feed {
+    /// Parse a feed, reading the `yt:*` elements by their resolved namespace rather than their prefix
+    pub fn from_xml(xml: &str) -> Result<Feed, DeError> {
+        let mut feed = quick_xml::de::from_str::<Feed>(xml)?;
+
+        let mut video_id = None;
+        let mut channel_id = None;
+
+        let mut reader = NsReader::from_str(xml);
+        loop {
+            match reader.read_resolved_event()? {
+                (ResolveResult::Bound(Namespace(YOUTUBE_NAMESPACE)), Event::Start(start)) => {
+                    let text = reader.read_text(start.name())?.trim().to_owned();
+
+                    match start.local_name().as_ref() {
+                        b"videoId" => video_id = Some(text),
+                        b"channelId" => channel_id = Some(text),
+                        _ => {}
+                    }
+                }
+                (_, Event::Eof) => break,
+                _ => {}
+            }
+        }
+
+        // Keep the unprefixed elements that serde found if the namespaced ones are missing
+        match video_id {
+            Some(video_id) => feed.entry.video_id = video_id,
+            None => tracing::debug!("feed has no namespaced video id"),
+        }
+        match channel_id {
+            Some(channel_id) => feed.entry.channel_id = channel_id,
+            None => tracing::debug!("feed has no namespaced channel id"),
+        }
+
+        Ok(feed)
+    }
+
+    pub fn entry(&self) -> &Entry {
+        &self.entry
+    }
+
+    pub fn into_entry(self) -> Entry {
+        self.entry
+    }
+
+    pub fn channel_id(&self) -> &str {
+        &self.entry.channel_id
+    }
+
+    pub fn video_id(&self) -> &str {
+        &self.entry.video_id
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Entry {
     pub id: String,
+    /// Replaced by the namespaced element in [`Feed::from_xml`]
     #[serde(rename = "yt:videoId")]
     #[serde(alias = "videoId")] // quick_xml ignores namespace prefixes with serde
     pub video_id: String,
+    /// Replaced by the namespaced element in [`Feed::from_xml`]
     #[serde(rename = "yt:channelId")]
     #[serde(alias = "channelId")] // quick_xml ignores namespace prefixes with serde
     pub channel_id: String,
     pub title: String,
     pub published: Timestamp,
     pub updated: Timestamp,
+    /// Not included in every feed
+    #[serde(rename = "media:group")]
+    #[serde(alias = "group")] // quick_xml ignores namespace prefixes with serde
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_group: Option<MediaGroup>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MediaGroup {
+    #[serde(rename = "media:thumbnail")]
+    #[serde(alias = "thumbnail")] // quick_xml ignores namespace prefixes with serde
+    pub thumbnail: Option<MediaThumbnail>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct MediaThumbnail {
+    #[serde(rename = "@width")]
+    pub width: u32,
+    #[serde(rename = "@height")]
+    pub height: u32,
+}
+
+pub fn is_valid_video_id(video_id: &str) -> bool {
+    video_id.len() == 11
+        && video_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn is_valid_channel_id(channel_id: &str) -> bool {
+    channel_id.len() == 24 && channel_id.starts_with("UC")
+}
+
+impl Entry {
+    /// Shorts have portrait thumbnails, so a taller than wide thumbnail hints that the video is
+    /// a short. [`None`] if there is no hint either way
+    pub fn is_short_hint(&self) -> Option<bool> {
+        let thumbnail = self.media_group.as_ref()?.thumbnail?;
+
+        (thumbnail.height > thumbnail.width).then_some(true)
+    }
+
+    pub fn validate(&self) -> Result<(), EntryValidationError> {
+        if !is_valid_video_id(&self.video_id) {
+            return Err(EntryValidationError::InvalidVideoId);
+        }
+
+        if !is_valid_channel_id(&self.channel_id) {
+            return Err(EntryValidationError::InvalidChannelId);
+        }
+
+        if self.title.is_empty() {
+            return Err(EntryValidationError::EmptyTitle);
+        }
+
+        if self.published > self.updated {
+            return Err(EntryValidationError::PublishedAfterUpdated);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryValidationError {
+    InvalidVideoId,
+    InvalidChannelId,
+    EmptyTitle,
+    PublishedAfterUpdated,
 }
+
+impl Display for EntryValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntryValidationError::InvalidVideoId => write!(f, "invalid video id"),
+            EntryValidationError::InvalidChannelId => write!(f, "invalid channel id"),
+            EntryValidationError::EmptyTitle => write!(f, "empty title"),
+            EntryValidationError::PublishedAfterUpdated => {
+                write!(f, "published timestamp is after updated timestamp")
+            }
+        }
+    }
+}
+
+impl Error for EntryValidationError {}
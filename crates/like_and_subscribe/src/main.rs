@@ -1,34 +1,58 @@
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
 
 use color_eyre::eyre::Context;
+use jiff::Timestamp;
 use mail_send::Credentials;
 use migration::{Migrator, MigratorTrait as _};
 use reqwest::redirect::Policy;
 use sea_orm::{ConnectOptions, Database, DatabaseConnection};
-use tokio::{signal::unix::SignalKind, sync::Notify};
+use tokio::{signal::unix::SignalKind, sync::Notify, task::JoinHandle};
 use tokio_util::{sync::CancellationToken, task::TaskTracker};
 use tower::ServiceBuilder;
+use tracing::Instrument as _;
 use tracing_error::ErrorLayer;
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt as _, util::SubscriberInitExt as _};
 
 use crate::{
     actor::{
+        channel_metadata::channel_metadata_refresh,
         email::email_sender,
+        inactive_channels::inactive_channel_check,
+        playlist::playlist_manager,
+        pool_stats::pool_stats_logger,
         pubsubhubbub::{queue::pubsub_queue_consumer, refresh::pubsub_refresh},
         subscription::subscription_manager,
+        task_metrics::task_metrics_watchdog,
+        video::video_queue_consumer,
+        video_queue_cleanup::video_queue_cleanup,
         web::web_server,
     },
+    alert::AlertSender,
+    config::Config,
+    database::VideoQueue,
     oauth::TokenManager,
+    pool_stats::PoolStats,
+    task_metrics::{RunningTasks, TaskMonitors},
 };
 
-//  mod playlist;
 mod actor;
+mod alert;
+mod config;
 mod database;
 mod feed;
 mod oauth;
+mod playlist;
+mod pool_stats;
+mod quota;
+mod task_metrics;
+mod util;
 
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
+    // Load environment variables from a local .env file during development
+    #[cfg(debug_assertions)]
+    dotenvy::dotenv().ok();
+
     color_eyre::install()?;
 
     tracing_subscriber::registry()
@@ -51,65 +75,62 @@ async fn main() -> color_eyre::Result<()> {
     tracing::warn!("a");
     tracing::error!("a");
 
-    let google_client_id = oauth2::ClientId::new(
-        std::env::var("GOOGLE_CLIENT_ID").wrap_err("unable to read GOOGLE_CLIENT_ID env var")?,
-    );
-    let google_client_secret = oauth2::ClientSecret::new(
-        std::env::var("GOOGLE_CLIENT_SECRET")
-            .wrap_err("unable to read GOOGLE_CLIENT_SECRET env var")?,
-    );
-
-    let email_credentials = {
-        Credentials::new(
-            std::env::var("ALERTS_SMTP_USERNAME")
-                .wrap_err("unable to read ALERTS_SMTP_USERNAME env var")?,
-            std::env::var("ALERTS_SMTP_PASSWORD")
-                .wrap_err("unable to read ALERTS_SMTP_PASSWORD env var")?,
-        )
-    };
+    let config = Arc::new(Config::from_env()?);
 
-    let playlist_id = std::env::var("YOUTUBE_PLAYLIST_ID")
-        .wrap_err("Unable to read YOUTUBE_PLAYLIST_ID env var")?;
+    let email_credentials =
+        Credentials::new(config.smtp_username.clone(), config.smtp_password.clone());
 
-    let hostname = std::env::var("HOSTNAME").wrap_err("Unable to read HOSTNAME env var")?;
+    let pool_stats = PoolStats::default();
 
     let client = reqwest::ClientBuilder::new()
         .https_only(true)
+        .connection_verbose(cfg!(debug_assertions))
         .connector_layer(
             ServiceBuilder::new()
+                .layer(pool_stats.layer())
                 .concurrency_limit(10)
                 .buffer(1024)
-                .rate_limit(5, Duration::from_secs(10)), // TODO: does this mean 5 sets of 10?
+                .rate_limit(
+                    config.http_rate_limit_count,
+                    config.http_rate_limit_period(),
+                ), // TODO: does this mean 5 sets of 10?
         )
         .redirect(Policy::none())
         .build()
         .wrap_err("Unable to setup reqwest client")?;
 
-    let database: DatabaseConnection = Database::connect(ConnectOptions::new(
-        std::env::var("DATABASE_URL").wrap_err("DATABASE_URL not set")?,
-    ))
-    .await
-    .wrap_err("unable to open database file")?;
+    let database: DatabaseConnection =
+        Database::connect(ConnectOptions::new(config.database_url.clone()))
+            .await
+            .wrap_err("unable to open database file")?;
 
     // Apply all pending migrations
     Migrator::up(&database, None).await?;
 
+    // Nothing can still be processing from a previous run
+    let cleared = VideoQueue::clear_stale_locks(&database, Timestamp::now()).await?;
+    if cleared > 0 {
+        tracing::warn!(cleared, "cleared videos left processing by a previous run");
+    }
+
     // TODO: some way to verify that the subscriptions are actually subscribed, maybe once a day?
     // https://pubsubhubbub.appspot.com/subscription-details?hub.callback=https%3A%2F%2Flenovo-fedora.taila5e2a.ts.net%2Fpubsub&hub.topic=https%3A%2F%2Fwww.youtube.com%2Fxml%2Ffeeds%2Fvideos.xml%3Fchannel_id%3DUCHtv-7yDeac7OSfPJA_a6aA&hub.secret=
 
-    let pubsubhubbub_callback = format!("https://{hostname}/pubsub");
+    let pubsubhubbub_callback = config.pubsub_callback();
 
     let subscriptions_queue_notify = Arc::new(Notify::const_new());
     let video_queue_notify = Arc::new(Notify::const_new());
 
     let (email_send_tx, email_send_rx) = tokio::sync::mpsc::channel(1);
 
+    let alerts = AlertSender::new(email_send_tx.clone());
+
     let token_manager = TokenManager::init(
         database.clone(),
-        google_client_id,
-        google_client_secret,
-        hostname.clone(),
-        email_send_tx,
+        oauth2::ClientId::new(config.google_client_id.clone()),
+        oauth2::ClientSecret::new(config.google_client_secret.clone()),
+        config.hostname.clone(),
+        alerts.clone(),
     )
     .await
     .wrap_err("unable to initialize the token manager")?;
@@ -117,44 +138,167 @@ async fn main() -> color_eyre::Result<()> {
     let shutdown = CancellationToken::new();
 
     let tasks = TaskTracker::new();
+    let task_monitors = TaskMonitors::default();
+    let running_tasks = RunningTasks::default();
 
     // Unauthenticated services
-    let mut web_server_task = tasks.spawn(web_server(
-        shutdown.clone(),
-        database.clone(),
-        video_queue_notify.clone(),
-        token_manager.clone(),
-    ));
-    let mut pubsubhubbub_queue_task = tasks.spawn(pubsub_queue_consumer(
-        shutdown.clone(),
-        database.clone(),
-        subscriptions_queue_notify.clone(),
-        client.clone(),
-        pubsubhubbub_callback,
-    ));
-    let mut pubsubhubbub_refresh_task = tasks.spawn(pubsub_refresh(
-        shutdown.clone(),
-        database.clone(),
-        subscriptions_queue_notify.clone(),
-    ));
+    let mut web_server_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "web_server",
+        web_server(
+            shutdown.clone(),
+            database.clone(),
+            video_queue_notify.clone(),
+            subscriptions_queue_notify.clone(),
+            token_manager.clone(),
+            task_monitors.clone(),
+            running_tasks.clone(),
+            email_send_tx,
+            config.clone(),
+            client.clone(),
+        ),
+    );
+    let mut pubsubhubbub_queue_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "pubsubhubbub_queue",
+        pubsub_queue_consumer(
+            shutdown.clone(),
+            database.clone(),
+            subscriptions_queue_notify.clone(),
+            client.clone(),
+            pubsubhubbub_callback,
+            config.clone(),
+            alerts.clone(),
+        ),
+    );
+    let mut pubsubhubbub_refresh_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "pubsubhubbub_refresh",
+        pubsub_refresh(
+            shutdown.clone(),
+            database.clone(),
+            subscriptions_queue_notify.clone(),
+            config.clone(),
+            alerts.clone(),
+        ),
+    );
 
     // Oauth service
     // let mut oauth_task = tasks.spawn(async {});
-    let mut email_task = tasks.spawn(email_sender(
-        shutdown.clone(),
-        email_credentials,
-        email_send_rx,
-    ));
+    let mut email_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "email",
+        email_sender(
+            shutdown.clone(),
+            config.smtp_host.clone(),
+            config.smtp_port,
+            email_credentials,
+            config.smtp_max_retries,
+            email_send_rx,
+        ),
+    );
 
     // Authenticated services
-    let mut subscription_task = tasks.spawn(subscription_manager(
-        shutdown.clone(),
-        database.clone(),
-        subscriptions_queue_notify.clone(),
-        client.clone(),
-        token_manager,
-    ));
-    // let mut video_task = tasks.spawn(async {});
+    let mut subscription_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "subscription",
+        subscription_manager(
+            shutdown.clone(),
+            database.clone(),
+            subscriptions_queue_notify.clone(),
+            client.clone(),
+            token_manager.clone(),
+            config.clone(),
+            alerts.clone(),
+        ),
+    );
+    let mut video_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "video",
+        video_queue_consumer(
+            shutdown.clone(),
+            database.clone(),
+            video_queue_notify.clone(),
+            client.clone(),
+            token_manager.clone(),
+            config.clone(),
+            alerts.clone(),
+        ),
+    );
+    let mut playlist_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "playlist",
+        playlist_manager(
+            shutdown.clone(),
+            database.clone(),
+            client.clone(),
+            token_manager.clone(),
+            config.clone(),
+            alerts.clone(),
+        ),
+    );
+    let mut channel_metadata_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "channel_metadata",
+        channel_metadata_refresh(shutdown.clone(), database.clone(), client, token_manager),
+    );
+    let mut inactive_channels_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "inactive_channels",
+        inactive_channel_check(
+            shutdown.clone(),
+            database.clone(),
+            alerts,
+            config.inactive_channel_threshold(),
+        ),
+    );
+    let mut video_queue_cleanup_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "video_queue_cleanup",
+        video_queue_cleanup(
+            shutdown.clone(),
+            database.clone(),
+            config.video_queue_retention(),
+        ),
+    );
+
+    // Diagnostics
+    let mut task_metrics_task = spawn_named(
+        &tasks,
+        &task_monitors,
+        &running_tasks,
+        "task_metrics",
+        task_metrics_watchdog(shutdown.clone(), task_monitors.clone()),
+    );
+    let mut pool_stats_task = config.pool_stats_enabled().then(|| {
+        spawn_named(
+            &tasks,
+            &task_monitors,
+            &running_tasks,
+            "pool_stats",
+            pool_stats_logger(shutdown.clone(), pool_stats),
+        )
+    });
 
     // Shutdown signals
     let mut sigint_task = tokio::signal::unix::signal(SignalKind::interrupt()).unwrap();
@@ -189,7 +333,14 @@ async fn main() -> color_eyre::Result<()> {
         result = &mut email_task => tracing::error!(?result, "email task exited"),
 
         result = &mut subscription_task => tracing::error!(?result, "subscription task exited"),
-        // result = &mut video_task => tracing::error!(?result, "video task exited"),
+        result = &mut video_task => tracing::error!(?result, "video task exited"),
+        result = &mut playlist_task => tracing::error!(?result, "playlist task exited"),
+        result = &mut channel_metadata_task => tracing::error!(?result, "channel metadata task exited"),
+        result = &mut inactive_channels_task => tracing::error!(?result, "inactive channels task exited"),
+        result = &mut video_queue_cleanup_task => tracing::error!(?result, "video queue cleanup task exited"),
+
+        result = &mut task_metrics_task => tracing::error!(?result, "task metrics task exited"),
+        result = async { pool_stats_task.as_mut().expect("pool stats task should be spawned").await }, if pool_stats_task.is_some() => tracing::error!(?result, "pool stats task exited"),
 
         _ = shutdown_signal() => tracing::warn!("User requested exit"),
     }
@@ -202,8 +353,33 @@ async fn main() -> color_eyre::Result<()> {
     // Wait for clean shutdown, or next interrupt
     tokio::select! {
         () = tasks.wait() => tracing::info!("exited gracefully"),
+        () = tokio::time::sleep(config.shutdown_timeout()) => tracing::warn!("timed out waiting for clean shutdown"),
         _ = shutdown_signal() => tracing::warn!("user sent second exit request during clean shutown"),
     }
 
     Ok(())
 }
+
+/// Spawn a task onto the tracker, recording whether it is still running for the health check
+fn spawn_named<F>(
+    tracker: &TaskTracker,
+    monitors: &TaskMonitors,
+    running_tasks: &RunningTasks,
+    name: &'static str,
+    future: F,
+) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let running = running_tasks.insert(name);
+
+    tracker.spawn(
+        monitors
+            .instrument(name, async move {
+                let _running = running;
+                future.await
+            })
+            .instrument(tracing::info_span!("task", name)),
+    )
+}
@@ -2,7 +2,6 @@ use std::sync::Arc;
 
 use color_eyre::eyre::{Context, ContextCompat};
 use jiff::{SignedDuration, Timestamp};
-use mail_send::mail_builder::MessageBuilder;
 use oauth2::{
     AccessToken, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken,
     EmptyExtraTokenFields, EndpointNotSet, EndpointSet, RedirectUrl, RevocationUrl,
@@ -10,9 +9,12 @@ use oauth2::{
     basic::{BasicClient, BasicTokenType},
 };
 use sea_orm::{DatabaseConnection, DbErr};
-use tokio::sync::{Mutex, Notify, mpsc};
+use tokio::sync::{Mutex, MutexGuard, Notify, watch};
 
-use crate::database::{Authentication, OAuth};
+use crate::{
+    alert::{AlertKind, AlertSender},
+    database::{Authentication, OAuth},
+};
 
 #[derive(Clone)]
 pub struct TokenManager {
@@ -24,7 +26,7 @@ impl TokenManager {
         client_id: ClientId,
         client_secret: ClientSecret,
         hostname: String,
-        mail_send: mpsc::Sender<MessageBuilder<'static>>,
+        alerts: AlertSender,
     ) -> Result<Self, DbErr> {
         let oauth_client = BasicClient::new(client_id)
             .set_client_secret(client_secret)
@@ -49,12 +51,13 @@ impl TokenManager {
             inner: Arc::new(TokenManagerInner {
                 oauth_client,
                 reqwest_client,
-                mail_send,
+                alerts,
                 current_token: Mutex::new(match OAuth::get_token(&database).await? {
                     Some(t) => TokenStatus::Existing(t),
                     None => TokenStatus::Missing { alerted: false },
                 }),
                 notify: Notify::new(),
+                access_token: watch::Sender::new(None),
                 database,
             }),
         })
@@ -72,6 +75,9 @@ impl TokenManager {
         let authentication = Authentication::from_token_response(token_response)?;
 
         *self.inner.current_token.lock().await = TokenStatus::Existing(authentication.clone());
+        self.inner
+            .access_token
+            .send_replace(Some(authentication.access_token.clone()));
         tracing::trace!("notifying wait_for_token waiters");
         self.inner.notify.notify_waiters();
         OAuth::save_token(&self.inner.database, authentication)
@@ -82,72 +88,106 @@ impl TokenManager {
     }
 
     pub async fn wait_for_token(&self) -> Result<AccessToken, DbErr> {
-        loop {
-            let mut token = self.inner.current_token.lock().await;
-
-            match &mut *token {
-                TokenStatus::Existing(authentication) => {
-                    if Timestamp::now().duration_until(authentication.expires_at)
-                        >= SignedDuration::ZERO
-                    {
-                        return Ok(authentication.access_token.clone());
-                    }
+        let mut access_token = self.inner.access_token.subscribe();
 
-                    let refresh_result = self
-                        .inner
-                        .oauth_client
-                        .exchange_refresh_token(&authentication.refresh_token)
-                        // Request refresh token
-                        .add_extra_param("access_type", "offline")
-                        .request_async(&self.inner.reqwest_client)
-                        .await;
-
-                    match refresh_result {
-                        Ok(token_response) => {
-                            let authentication =
-                                Authentication::from_token_response(token_response);
-
-                            match authentication {
-                                Ok(authentication) => {
-                                    let access_token = authentication.access_token.clone();
-                                    OAuth::save_token(&self.inner.database, authentication).await?;
-
-                                    return Ok(access_token);
-                                }
-                                Err(error) => {
-                                    tracing::error!(%error, "failed to handle token response");
-                                    OAuth::remove_token(&self.inner.database).await?;
-                                    self.send_email().await;
-                                }
-                            }
-                        }
-                        Err(error) => {
-                            tracing::error!(%error, "failed to refresh access token");
-                            OAuth::remove_token(&self.inner.database).await?;
-                            self.send_email().await;
-                        }
-                    }
-                }
-                TokenStatus::Missing { alerted: true } => {}
-                TokenStatus::Missing {
-                    alerted: alerted @ false,
-                } => {
-                    self.send_email().await;
-                    *alerted = true;
+        loop {
+            let Ok(token) = self.inner.current_token.try_lock() else {
+                // Another caller may be refreshing the token, use its result instead of refreshing again
+                tracing::trace!("waiting for concurrent token check");
+                access_token
+                    .changed()
+                    .await
+                    .expect("access token sender should not be dropped");
+
+                if let Some(access_token) = access_token.borrow_and_update().clone() {
+                    return Ok(access_token);
                 }
+
+                continue;
             };
 
+            // Broadcast once the lock is released, so that callers waiting above always see it
+            let result = self.check_token(token).await;
+            self.inner
+                .access_token
+                .send_replace(result.as_ref().ok().cloned().flatten());
+
+            if let Some(access_token) = result? {
+                return Ok(access_token);
+            }
+
             // Wait for token to be loaded
-            drop(token);
             tracing::debug!("waiting for new token to be obtained");
             self.inner.notify.notified().await;
             tracing::debug!("token obtained");
         }
     }
 
+    /// Refresh the token if it has expired, returning [`None`] if a new token has to be obtained
+    async fn check_token(
+        &self,
+        mut token: MutexGuard<'_, TokenStatus>,
+    ) -> Result<Option<AccessToken>, DbErr> {
+        match &mut *token {
+            TokenStatus::Existing(authentication) => {
+                if Timestamp::now().duration_until(authentication.expires_at)
+                    >= SignedDuration::ZERO
+                {
+                    return Ok(Some(authentication.access_token.clone()));
+                }
+
+                let refresh_result = self
+                    .inner
+                    .oauth_client
+                    .exchange_refresh_token(&authentication.refresh_token)
+                    // Request refresh token
+                    .add_extra_param("access_type", "offline")
+                    .request_async(&self.inner.reqwest_client)
+                    .await;
+
+                match refresh_result {
+                    Ok(token_response) => {
+                        let authentication = Authentication::from_token_response(token_response);
+
+                        match authentication {
+                            Ok(authentication) => {
+                                let access_token = authentication.access_token.clone();
+                                OAuth::save_token(&self.inner.database, authentication.clone())
+                                    .await?;
+                                *token = TokenStatus::Existing(authentication);
+
+                                return Ok(Some(access_token));
+                            }
+                            Err(error) => {
+                                tracing::error!(%error, "failed to handle token response");
+                                OAuth::remove_token(&self.inner.database).await?;
+                                self.send_email().await;
+                                *token = TokenStatus::Missing { alerted: true };
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        tracing::error!(%error, "failed to refresh access token");
+                        OAuth::remove_token(&self.inner.database).await?;
+                        self.send_email().await;
+                        *token = TokenStatus::Missing { alerted: true };
+                    }
+                }
+            }
+            TokenStatus::Missing { alerted: true } => {}
+            TokenStatus::Missing {
+                alerted: alerted @ false,
+            } => {
+                self.send_email().await;
+                *alerted = true;
+            }
+        };
+
+        Ok(None)
+    }
+
     // TODO: explain the reason for the re-auth
     async fn send_email(&self) {
-        tracing::info!("Queuing email");
         let (authorize_url, _) = self
             .inner
             .oauth_client
@@ -163,11 +203,12 @@ impl TokenManager {
             .add_extra_param("prompt", "consent")
             .url();
 
-        let message = MessageBuilder::new()
-            .subject("Re-authenticate with google to continue")
-            .html_body(format!(r##"<a href="{0}">{0}</a>"##, authorize_url));
-
-        self.inner.mail_send.send(message).await.unwrap();
+        self.inner
+            .alerts
+            .send_alert(AlertKind::Reauthenticate {
+                url: authorize_url.to_string(),
+            })
+            .await;
     }
 }
 
@@ -192,13 +233,15 @@ impl Authentication {
 struct TokenManagerInner {
     oauth_client:
         BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointSet, EndpointSet>,
-    mail_send: mpsc::Sender<MessageBuilder<'static>>,
+    alerts: AlertSender,
 
     reqwest_client: reqwest::Client,
     database: DatabaseConnection,
 
     current_token: Mutex<TokenStatus>,
     notify: Notify,
+    /// Result of the last token check, for callers that waited on a concurrent check
+    access_token: watch::Sender<Option<AccessToken>>,
 }
 
 enum TokenStatus {
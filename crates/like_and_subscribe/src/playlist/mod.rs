@@ -0,0 +1,341 @@
+use google_youtube3::api::{
+    PlaylistItem, PlaylistItemListResponse, PlaylistItemSnippet, ResourceId, VideoListResponse,
+};
+use jiff::Timestamp;
+use oauth2::AccessToken;
+use reqwest::StatusCode;
+use sea_orm::DatabaseConnection;
+
+use crate::quota::{ApiCall, send_recorded};
+
+pub mod shorts;
+
+const PLAYLIST_ITEMS_URL: &str = "https://www.googleapis.com/youtube/v3/playlistItems";
+const VIDEOS_URL: &str = "https://www.googleapis.com/youtube/v3/videos";
+
+/// Error reason given by the api when the daily quota has been used up
+const QUOTA_EXCEEDED_REASON: &str = "quotaExceeded";
+
+#[derive(Debug)]
+pub enum ApiError {
+    Request(reqwest::Error),
+    QuotaExceeded,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Request(error) => error.fmt(f),
+            ApiError::QuotaExceeded => f.write_str("youtube api quota exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ApiError::Request(error) => Some(error),
+            ApiError::QuotaExceeded => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(error: reqwest::Error) -> Self {
+        ApiError::Request(error)
+    }
+}
+
+/// Like [`reqwest::Response::error_for_status`], but distinguishes running out of quota from other errors
+async fn error_for_status(response: reqwest::Response) -> Result<reqwest::Response, ApiError> {
+    let Err(error) = response.error_for_status_ref() else {
+        return Ok(response);
+    };
+
+    if response.status() == StatusCode::FORBIDDEN
+        && response
+            .text()
+            .await
+            .is_ok_and(|body| body.contains(QUOTA_EXCEEDED_REASON))
+    {
+        return Err(ApiError::QuotaExceeded);
+    }
+
+    Err(ApiError::Request(error))
+}
+
+/// Check if the playlist already contains the given video
+pub async fn contains_video(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    token: &AccessToken,
+    playlist_id: &str,
+    video_id: &str,
+) -> Result<bool, ApiError> {
+    let request = client
+        .get(PLAYLIST_ITEMS_URL)
+        .query(&[
+            ("part", "contentDetails"),
+            ("playlistId", playlist_id),
+            ("videoId", video_id),
+        ])
+        .bearer_auth(token.secret());
+
+    let response = send_recorded(
+        database,
+        request,
+        ApiCall {
+            endpoint: "playlistItems.list",
+            quota_cost: 1,
+            video_id: Some(video_id),
+            channel_id: None,
+        },
+    )
+    .await?;
+
+    let items = error_for_status(response)
+        .await?
+        .json::<PlaylistItemListResponse>()
+        .await?;
+
+    Ok(items.items.into_iter().flatten().any(|item| {
+        item.content_details
+            .and_then(|details| details.video_id)
+            .as_deref()
+            == Some(video_id)
+    }))
+}
+
+/// Append the given video to the end of the playlist
+pub async fn insert_video(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    token: &AccessToken,
+    playlist_id: &str,
+    video_id: &str,
+) -> Result<PlaylistItem, ApiError> {
+    let request = client
+        .post(PLAYLIST_ITEMS_URL)
+        .query(&[("part", "snippet")])
+        .bearer_auth(token.secret())
+        .json(&PlaylistItem {
+            snippet: Some(PlaylistItemSnippet {
+                playlist_id: Some(playlist_id.to_owned()),
+                resource_id: Some(ResourceId {
+                    kind: Some("youtube#video".to_owned()),
+                    video_id: Some(video_id.to_owned()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+    let response = send_recorded(
+        database,
+        request,
+        ApiCall {
+            endpoint: "playlistItems.insert",
+            quota_cost: 50,
+            video_id: Some(video_id),
+            channel_id: None,
+        },
+    )
+    .await?;
+
+    Ok(error_for_status(response)
+        .await?
+        .json::<PlaylistItem>()
+        .await?)
+}
+
+/// Privacy status of the video, such as `public`, `unlisted` or `private`. [`None`] if the video
+/// could not be found
+pub async fn video_visibility(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    token: &AccessToken,
+    video_id: &str,
+) -> Result<Option<String>, ApiError> {
+    let request = client
+        .get(VIDEOS_URL)
+        .query(&[("part", "status"), ("id", video_id)])
+        .bearer_auth(token.secret());
+
+    let response = send_recorded(
+        database,
+        request,
+        ApiCall {
+            endpoint: "videos.list",
+            quota_cost: 1,
+            video_id: Some(video_id),
+            channel_id: None,
+        },
+    )
+    .await?;
+
+    let videos = error_for_status(response)
+        .await?
+        .json::<VideoListResponse>()
+        .await?;
+
+    Ok(videos
+        .items
+        .into_iter()
+        .flatten()
+        .find_map(|video| video.status?.privacy_status))
+}
+
+pub struct PlaylistVideo {
+    /// Id of the playlist item, needed to remove the video from the playlist
+    pub item_id: String,
+    pub video_id: String,
+}
+
+/// Most recent upload of a channel
+#[derive(Debug)]
+pub struct LatestUpload {
+    pub video_id: String,
+    pub title: String,
+    pub published: Timestamp,
+}
+
+/// Find the most recent video in the channel's uploads playlist
+pub async fn latest_upload(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    token: &AccessToken,
+    channel_id: &str,
+) -> Result<Option<LatestUpload>, ApiError> {
+    // Every channel has an uploads playlist with the same id, apart from the prefix
+    let Some(uploads_playlist_id) = channel_id.strip_prefix("UC").map(|id| format!("UU{id}"))
+    else {
+        return Ok(None);
+    };
+
+    let request = client
+        .get(PLAYLIST_ITEMS_URL)
+        .query(&[
+            ("part", "snippet,contentDetails"),
+            ("playlistId", uploads_playlist_id.as_str()),
+            ("maxResults", "1"),
+        ])
+        .bearer_auth(token.secret());
+
+    let response = send_recorded(
+        database,
+        request,
+        ApiCall {
+            endpoint: "playlistItems.list",
+            quota_cost: 1,
+            video_id: None,
+            channel_id: Some(channel_id),
+        },
+    )
+    .await?;
+
+    let items = error_for_status(response)
+        .await?
+        .json::<PlaylistItemListResponse>()
+        .await?;
+
+    Ok(items.items.into_iter().flatten().find_map(|item| {
+        let snippet = item.snippet?;
+        let content_details = item.content_details?;
+
+        Some(LatestUpload {
+            video_id: content_details.video_id?,
+            title: snippet.title?,
+            published: Timestamp::from_millisecond(
+                content_details
+                    .video_published_at
+                    .or(snippet.published_at)?
+                    .timestamp_millis(),
+            )
+            .ok()?,
+        })
+    }))
+}
+
+/// Every video in the playlist, in playlist order
+pub async fn list_videos(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    token: &AccessToken,
+    playlist_id: &str,
+) -> Result<Vec<PlaylistVideo>, ApiError> {
+    let mut videos = Vec::new();
+    let mut page_token = None;
+
+    loop {
+        let mut request = client
+            .get(PLAYLIST_ITEMS_URL)
+            .query(&[
+                ("part", "contentDetails"),
+                ("playlistId", playlist_id),
+                ("maxResults", "50"),
+            ])
+            .bearer_auth(token.secret());
+        if let Some(page_token) = &page_token {
+            request = request.query(&[("pageToken", page_token)]);
+        }
+
+        let response = send_recorded(
+            database,
+            request,
+            ApiCall {
+                endpoint: "playlistItems.list",
+                quota_cost: 1,
+                video_id: None,
+                channel_id: None,
+            },
+        )
+        .await?;
+
+        let page = error_for_status(response)
+            .await?
+            .json::<PlaylistItemListResponse>()
+            .await?;
+
+        videos.extend(page.items.into_iter().flatten().filter_map(|item| {
+            Some(PlaylistVideo {
+                item_id: item.id?,
+                video_id: item.content_details?.video_id?,
+            })
+        }));
+
+        page_token = page.next_page_token;
+        if page_token.is_none() {
+            break Ok(videos);
+        }
+    }
+}
+
+/// Remove the playlist item from its playlist
+pub async fn remove_video(
+    database: &DatabaseConnection,
+    client: &reqwest::Client,
+    token: &AccessToken,
+    video: &PlaylistVideo,
+) -> Result<(), ApiError> {
+    let request = client
+        .delete(PLAYLIST_ITEMS_URL)
+        .query(&[("id", &video.item_id)])
+        .bearer_auth(token.secret());
+
+    let response = send_recorded(
+        database,
+        request,
+        ApiCall {
+            endpoint: "playlistItems.delete",
+            quota_cost: 50,
+            video_id: Some(&video.video_id),
+            channel_id: None,
+        },
+    )
+    .await?;
+
+    error_for_status(response).await?;
+
+    Ok(())
+}
@@ -1,12 +1,17 @@
 use bstr::ByteSlice as _;
 use reqwest::header;
+use sea_orm::DatabaseConnection;
+
+use crate::quota::{ApiCall, send_recorded};
 
 #[cfg(test)]
 mod test {
     use std::time::Duration;
 
     use color_eyre::eyre::{Context as _, eyre};
+    use migration::{Migrator, MigratorTrait as _};
     use reqwest::redirect::Policy;
+    use sea_orm::Database;
     use tower::ServiceBuilder;
 
     use crate::playlist::shorts::check_redirect;
@@ -37,10 +42,15 @@ mod test {
             .build()
             .wrap_err("Unable to setup reqwest client")?;
 
+        let database = Database::connect("sqlite::memory:").await?;
+        Migrator::up(&database, None).await?;
+
         for &(video_id, expected_is_short) in VIDEO_IDS {
-            let is_short = check_redirect(video_id, &client).await.map_err(|err| {
-                eyre!("failed to check video {video_id}").wrap_err(eyre!("{err:?}"))
-            })?;
+            let is_short = check_redirect(&database, video_id, &client)
+                .await
+                .map_err(|err| {
+                    eyre!("failed to check video {video_id}").wrap_err(eyre!("{err:?}"))
+                })?;
 
             assert_eq!(is_short, expected_is_short)
         }
@@ -57,17 +67,22 @@ pub enum CheckRedirectError {
 }
 
 pub async fn check_redirect(
+    database: &DatabaseConnection,
     video_id: &str,
     client: &reqwest::Client,
 ) -> Result<bool, CheckRedirectError> {
-    let result = client
-        .execute(
-            client
-                .head(format!("https://www.youtube.com/shorts/{}", video_id))
-                .build()
-                .expect("request should be well formed"),
-        )
-        .await;
+    let result = send_recorded(
+        database,
+        client.head(format!("https://www.youtube.com/shorts/{}", video_id)),
+        ApiCall {
+            endpoint: "shorts",
+            // Not part of the data api
+            quota_cost: 0,
+            video_id: Some(video_id),
+            channel_id: None,
+        },
+    )
+    .await;
 
     let response = match result {
         Ok(response) => response,
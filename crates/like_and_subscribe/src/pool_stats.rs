@@ -0,0 +1,115 @@
+use std::{
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+use tower::{Layer, Service};
+
+/// Counts the connections opened by the http client
+///
+/// Reqwest does not expose the state of its connection pool, so this is measured
+/// from the connector instead. Every connection opened is one that could not be reused.
+#[derive(Clone, Default)]
+pub struct PoolStats {
+    inner: Arc<PoolStatsInner>,
+}
+
+#[derive(Default)]
+struct PoolStatsInner {
+    /// Waiting for a connection to be established, including waiting on the rate limit
+    pending: AtomicU64,
+    opened: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl PoolStats {
+    /// Connector layer recording into these stats
+    pub fn layer(&self) -> PoolStatsLayer {
+        PoolStatsLayer {
+            stats: self.clone(),
+        }
+    }
+
+    pub fn snapshot(&self) -> PoolStatsSnapshot {
+        PoolStatsSnapshot {
+            pending: self.inner.pending.load(Ordering::Relaxed),
+            opened: self.inner.opened.load(Ordering::Relaxed),
+            failed: self.inner.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PoolStatsSnapshot {
+    pub pending: u64,
+    pub opened: u64,
+    pub failed: u64,
+}
+
+#[derive(Clone)]
+pub struct PoolStatsLayer {
+    stats: PoolStats,
+}
+
+impl<S> Layer<S> for PoolStatsLayer {
+    type Service = PoolStatsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PoolStatsService {
+            inner,
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PoolStatsService<S> {
+    inner: S,
+    stats: PoolStats,
+}
+
+/// Stops counting a connection as pending once it is established, fails or is cancelled
+struct PendingGuard(PoolStats);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.0.inner.pending.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl<S, R> Service<R> for PoolStatsService<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        self.stats.inner.pending.fetch_add(1, Ordering::Relaxed);
+        let guard = PendingGuard(self.stats.clone());
+
+        let future = self.inner.call(request);
+
+        Box::pin(async move {
+            let result = future.await;
+
+            let counter = match result {
+                Ok(_) => &guard.0.inner.opened,
+                Err(_) => &guard.0.inner.failed,
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+
+            result
+        })
+    }
+}
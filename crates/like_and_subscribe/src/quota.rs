@@ -0,0 +1,49 @@
+use jiff::tz::TimeZone;
+use sea_orm::DatabaseConnection;
+
+use crate::database::YoutubeApiCalls;
+
+/// The daily quota resets at midnight Pacific time, so usage is bucketed into days there
+pub fn quota_time_zone() -> TimeZone {
+    TimeZone::get("America/Los_Angeles").expect("time zone database should be bundled")
+}
+
+/// A youtube request, recorded so that quota usage can be tracked
+pub struct ApiCall<'a> {
+    pub endpoint: &'static str,
+    /// Units of the daily quota used by the request
+    pub quota_cost: i32,
+    pub video_id: Option<&'a str>,
+    pub channel_id: Option<&'a str>,
+}
+
+/// Send the request, recording it whether or not a response was received
+///
+/// Failing to record the call does not fail the request
+pub async fn send_recorded(
+    database: &DatabaseConnection,
+    request: reqwest::RequestBuilder,
+    call: ApiCall<'_>,
+) -> reqwest::Result<reqwest::Response> {
+    let result = request.send().await;
+
+    let status_code = result
+        .as_ref()
+        .ok()
+        .map(|response| response.status().as_u16());
+
+    if let Err(error) = YoutubeApiCalls::record(
+        database,
+        call.endpoint,
+        call.video_id,
+        call.channel_id,
+        call.quota_cost,
+        status_code,
+    )
+    .await
+    {
+        tracing::warn!(%error, endpoint = call.endpoint, "failed to record youtube api call");
+    }
+
+    result
+}
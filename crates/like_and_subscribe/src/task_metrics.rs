@@ -0,0 +1,263 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use tokio_metrics::TaskMonitor;
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::task_metrics::{RunningTasks, TaskMonitors};
+
+    #[test]
+    fn test_running_task_removed_on_drop() {
+        let running = RunningTasks::default();
+
+        let guard = running.insert("actor");
+        assert!(running.contains("actor"));
+
+        drop(guard);
+        assert!(!running.contains("actor"));
+    }
+
+    #[tokio::test]
+    async fn test_instrument_records_polls() {
+        let monitors = TaskMonitors::default();
+
+        monitors
+            .instrument("blocking", async {
+                tokio::task::yield_now().await;
+                std::thread::sleep(Duration::from_millis(5));
+            })
+            .await;
+
+        let [metrics] = monitors.metrics()[..] else {
+            panic!("exactly one monitor should be registered");
+        };
+
+        assert_eq!(metrics.name, "blocking");
+        assert_eq!(metrics.poll_count, 2);
+        // The blocking poll is always slow, but a loaded machine can make the first one slow too
+        assert!((1..=2).contains(&metrics.slow_poll_count));
+        assert!(metrics.mean_poll_duration() >= Duration::from_micros(2500));
+        assert!(metrics.slow_poll_percentage() >= 50.0);
+
+        // Not active again until there are new polls
+        let [later] = monitors.metrics()[..] else {
+            panic!("exactly one monitor should be registered");
+        };
+
+        assert_eq!(later.last_active, metrics.last_active);
+    }
+}
+
+/// Polls taking longer than this are counted as slow
+const SLOW_POLL_THRESHOLD: Duration = Duration::from_micros(50);
+
+tokio::task_local! {
+    static ACTIVITY: Arc<Activity>;
+}
+
+/// Activity of an actor that is stamped by the actor itself, so that waiting for its next
+/// piece of work is not mistaken for being stuck
+struct Activity {
+    idle: AtomicBool,
+    last_active: Mutex<Instant>,
+}
+
+impl Activity {
+    fn stamp(&self, idle: bool) {
+        self.idle.store(idle, Ordering::Relaxed);
+        *self
+            .last_active
+            .lock()
+            .expect("mutex should not be poisoned") = Instant::now();
+    }
+}
+
+/// Marks the current actor as idle while waiting on the future, such as a sleep or notification
+///
+/// Does nothing outside of a task spawned through [`TaskMonitors::instrument`]
+pub async fn idle<F: IntoFuture>(future: F) -> F::Output {
+    struct Wake(Option<Arc<Activity>>);
+
+    impl Drop for Wake {
+        fn drop(&mut self) {
+            if let Some(activity) = &self.0 {
+                activity.stamp(false);
+            }
+        }
+    }
+
+    let activity = ACTIVITY.try_with(Arc::clone).ok();
+    if let Some(activity) = &activity {
+        activity.stamp(true);
+    }
+
+    // Stamped on drop as well, since the future is often cancelled by another `select!` branch
+    let _wake = Wake(activity);
+    future.await
+}
+
+/// Registry of the [`TaskMonitor`]s for every spawned actor
+#[derive(Clone, Default)]
+pub struct TaskMonitors {
+    monitors: Arc<Mutex<Vec<NamedTaskMonitor>>>,
+}
+
+struct NamedTaskMonitor {
+    name: &'static str,
+    monitor: TaskMonitor,
+    /// Poll count the last time the metrics were read, and when it last changed
+    last_poll_count: u64,
+    activity: Arc<Activity>,
+}
+
+impl TaskMonitors {
+    /// Record the poll metrics of the future under the given name, along with the activity
+    /// stamped by [`idle`]
+    pub fn instrument<F: Future>(
+        &self,
+        name: &'static str,
+        future: F,
+    ) -> impl Future<Output = F::Output> + use<F> {
+        let monitor = TaskMonitor::with_slow_poll_threshold(SLOW_POLL_THRESHOLD);
+        let activity = Arc::new(Activity {
+            idle: AtomicBool::new(false),
+            last_active: Mutex::new(Instant::now()),
+        });
+
+        self.monitors
+            .lock()
+            .expect("mutex should not be poisoned")
+            .push(NamedTaskMonitor {
+                name,
+                monitor: monitor.clone(),
+                last_poll_count: 0,
+                activity: activity.clone(),
+            });
+
+        monitor.instrument(ACTIVITY.scope(activity, future))
+    }
+
+    /// Tasks are only seen to be active when their metrics are read, so `last_active` is as
+    /// precise as the interval between calls
+    pub fn metrics(&self) -> Vec<TaskMetrics> {
+        self.monitors
+            .lock()
+            .expect("mutex should not be poisoned")
+            .iter_mut()
+            .map(|named| {
+                let metrics = named.monitor.cumulative();
+                let mut last_active = named
+                    .activity
+                    .last_active
+                    .lock()
+                    .expect("mutex should not be poisoned");
+
+                if metrics.total_poll_count != named.last_poll_count {
+                    named.last_poll_count = metrics.total_poll_count;
+                    *last_active = Instant::now();
+                }
+
+                TaskMetrics {
+                    name: named.name,
+                    last_active: *last_active,
+                    idle: named.activity.idle.load(Ordering::Relaxed),
+                    poll_count: metrics.total_poll_count,
+                    slow_poll_count: metrics.total_slow_poll_count,
+                    total_poll_duration: metrics.total_poll_duration,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TaskMetrics {
+    pub name: &'static str,
+    /// When the task was last seen to be polled, or created if it has not been polled yet
+    pub last_active: Instant,
+    /// The task is waiting inside of [`idle`] for its next piece of work
+    pub idle: bool,
+    pub poll_count: u64,
+    pub slow_poll_count: u64,
+    pub total_poll_duration: Duration,
+}
+
+impl TaskMetrics {
+    pub fn mean_poll_duration(&self) -> Duration {
+        self.total_poll_duration
+            .checked_div(self.poll_count.try_into().unwrap_or(u32::MAX))
+            .unwrap_or_default()
+    }
+
+    pub fn slow_poll_percentage(&self) -> f64 {
+        if self.poll_count == 0 {
+            return 0.0;
+        }
+
+        self.slow_poll_count as f64 / self.poll_count as f64 * 100.0
+    }
+
+    /// Metrics for the polls that happened since `earlier` was taken
+    pub fn since(&self, earlier: &TaskMetrics) -> TaskMetrics {
+        TaskMetrics {
+            name: self.name,
+            last_active: self.last_active,
+            idle: self.idle,
+            poll_count: self.poll_count - earlier.poll_count,
+            slow_poll_count: self.slow_poll_count - earlier.slow_poll_count,
+            total_poll_duration: self.total_poll_duration - earlier.total_poll_duration,
+        }
+    }
+}
+
+/// Names of the spawned tasks that have not exited yet
+#[derive(Clone, Default)]
+pub struct RunningTasks {
+    names: Arc<Mutex<HashSet<&'static str>>>,
+}
+
+impl RunningTasks {
+    /// Mark the task as running until the returned guard is dropped
+    pub fn insert(&self, name: &'static str) -> RunningTaskGuard {
+        self.names
+            .lock()
+            .expect("mutex should not be poisoned")
+            .insert(name);
+
+        RunningTaskGuard {
+            name,
+            names: self.names.clone(),
+        }
+    }
+
+    pub fn contains(&self, name: &'static str) -> bool {
+        self.names
+            .lock()
+            .expect("mutex should not be poisoned")
+            .contains(name)
+    }
+}
+
+/// Removes the task from [`RunningTasks`] when dropped, including when the task panics or is aborted
+pub struct RunningTaskGuard {
+    name: &'static str,
+    names: Arc<Mutex<HashSet<&'static str>>>,
+}
+
+impl Drop for RunningTaskGuard {
+    fn drop(&mut self) {
+        // Do not panic while unwinding from a panicking task
+        if let Ok(mut names) = self.names.lock() {
+            names.remove(self.name);
+        }
+    }
+}
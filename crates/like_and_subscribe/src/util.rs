@@ -0,0 +1,60 @@
+use std::pin::pin;
+
+#[cfg(test)]
+#[expect(
+    clippy::items_after_test_module,
+    reason = "tests are kept at the top of the file"
+)]
+mod test {
+    use std::future::{pending, ready};
+
+    use crate::util::race_fallback;
+
+    async fn later(value: bool) -> bool {
+        tokio::task::yield_now().await;
+        value
+    }
+
+    #[tokio::test]
+    async fn test_race_fallback_short_circuits() {
+        assert!(race_fallback(ready(true), pending(), |&value| value).await);
+        assert!(race_fallback(pending(), ready(true), |&value| value).await);
+    }
+
+    #[tokio::test]
+    async fn test_race_fallback_waits_for_other() {
+        assert!(race_fallback(ready(false), later(true), |&value| value).await);
+        assert!(race_fallback(later(true), ready(false), |&value| value).await);
+        assert!(!race_fallback(ready(false), later(false), |&value| value).await);
+    }
+}
+
+/// Run both futures concurrently, returning the first result that is definitive
+///
+/// If the first future to complete gives an indeterminate result, the other future is awaited and
+/// its result is returned regardless. A definitive result drops the other future.
+pub async fn race_fallback<T, F1, F2>(f1: F1, f2: F2, is_definitive: fn(&T) -> bool) -> T
+where
+    F1: Future<Output = T>,
+    F2: Future<Output = T>,
+{
+    let mut f1 = pin!(f1);
+    let mut f2 = pin!(f2);
+
+    tokio::select! {
+        result = &mut f1 => {
+            if is_definitive(&result) {
+                result
+            } else {
+                f2.await
+            }
+        }
+        result = &mut f2 => {
+            if is_definitive(&result) {
+                result
+            } else {
+                f1.await
+            }
+        }
+    }
+}
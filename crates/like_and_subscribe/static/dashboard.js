@@ -0,0 +1,131 @@
+const SVG_NS = "http://www.w3.org/2000/svg";
+const TIMELINE_ACTIONS = ["subscribe", "unsubscribe", "refresh"];
+
+async function drawTimeline(svg) {
+    const response = await fetch("/admin/api/timeline?days=30");
+    if (!response.ok) {
+        throw new Error(`${response.status} ${response.statusText}`);
+    }
+    const days = await response.json();
+
+    const width = svg.clientWidth;
+    const height = svg.clientHeight;
+    const labelHeight = 15;
+    const chartHeight = height - labelHeight;
+
+    const max = Math.max(1, ...days.map((day) => TIMELINE_ACTIONS.reduce((sum, action) => sum + day[action], 0)));
+    const barWidth = width / Math.max(days.length, 1);
+
+    svg.setAttribute("viewBox", `0 0 ${width} ${height}`);
+    svg.replaceChildren();
+
+    days.forEach((day, index) => {
+        const x = index * barWidth;
+        let y = chartHeight;
+
+        for (const action of TIMELINE_ACTIONS) {
+            const barHeight = (day[action] / max) * chartHeight;
+            y -= barHeight;
+
+            const rect = document.createElementNS(SVG_NS, "rect");
+            rect.setAttribute("class", action);
+            rect.setAttribute("x", x + 1);
+            rect.setAttribute("y", y);
+            rect.setAttribute("width", Math.max(barWidth - 2, 1));
+            rect.setAttribute("height", barHeight);
+
+            const title = document.createElementNS(SVG_NS, "title");
+            title.textContent = `${day.date}: ${day[action]} ${action}`;
+            rect.append(title);
+
+            svg.append(rect);
+        }
+
+        const label = document.createElementNS(SVG_NS, "text");
+        label.setAttribute("x", x + barWidth / 2);
+        label.setAttribute("y", height - 2);
+        label.setAttribute("text-anchor", "middle");
+        label.textContent = day.date.slice(5);
+        svg.append(label);
+    });
+}
+
+async function fillOverview(table) {
+    const response = await fetch("/admin/api/dashboard-data");
+    if (!response.ok) {
+        throw new Error(`${response.status} ${response.statusText}`);
+    }
+    const data = await response.json();
+
+    const values = {
+        known_channels_count: data.known_channels_count,
+        subscriptions_total: data.subscription_stats.total,
+        subscriptions_expiring: data.subscription_stats.expiring_in_1_day,
+        subscriptions_expired: data.subscription_stats.already_expired,
+        pending_videos: data.queue_depth.videos,
+        pending_subscriptions: data.queue_depth.subscriptions,
+    };
+
+    for (const cell of table.querySelectorAll("td[data-overview]")) {
+        cell.textContent = values[cell.dataset.overview];
+    }
+}
+
+document.addEventListener("DOMContentLoaded", () => {
+    const overview = document.getElementById("table:overview");
+    if (overview) {
+        fillOverview(overview).catch((error) => console.error("Failed to load overview", error));
+    }
+
+    const timeline = document.getElementById("timeline");
+    if (timeline) {
+        drawTimeline(timeline).catch((error) => console.error("Failed to draw timeline", error));
+    }
+
+    for (const badge of document.querySelectorAll("button.badge[data-shorts-channel]")) {
+        badge.addEventListener("click", async () => {
+            const isShortsChannel = badge.dataset.shortsChannel !== "true";
+
+            badge.disabled = true;
+            try {
+                const response = await fetch(`/admin/api/channels/${encodeURIComponent(badge.dataset.channelId)}`, {
+                    method: "PATCH",
+                    headers: { "Content-Type": "application/json" },
+                    body: JSON.stringify({ is_shorts_channel: isShortsChannel }),
+                });
+
+                if (!response.ok) {
+                    throw new Error(`${response.status} ${response.statusText}`);
+                }
+
+                badge.dataset.shortsChannel = String(isShortsChannel);
+                badge.classList.toggle("active", isShortsChannel);
+                badge.textContent = isShortsChannel ? "Shorts" : "Videos";
+            } catch (error) {
+                alert(`Failed to update channel: ${error}`);
+            } finally {
+                badge.disabled = false;
+            }
+        });
+    }
+
+    for (const button of document.querySelectorAll("button[data-blacklisted-video-id]")) {
+        button.addEventListener("click", async () => {
+            button.disabled = true;
+            try {
+                const response = await fetch(`/admin/api/video-blacklist/${encodeURIComponent(button.dataset.blacklistedVideoId)}`, {
+                    method: "DELETE",
+                });
+
+                if (!response.ok) {
+                    throw new Error(`${response.status} ${response.statusText}`);
+                }
+
+                button.closest("tr").remove();
+            } catch (error) {
+                alert(`Failed to unblock video: ${error}`);
+                button.disabled = false;
+            }
+        });
+    }
+});
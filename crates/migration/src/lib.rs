@@ -1,12 +1,92 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20250901_000001_create_tables;
+mod m20250901_000002_create_audit_log;
+mod m20250901_000003_create_channel_groups;
+mod m20250901_000004_add_known_channels_is_shorts_channel;
+mod m20250901_000005_add_known_channels_video_age_threshold;
+mod m20250901_000006_add_known_channels_last_video_at;
+mod m20250901_000007_add_known_channels_handle;
+mod m20250901_000008_create_subscription_sync_runs;
+mod m20250901_000009_add_subscription_secrets;
+mod m20250901_000010_create_failed_feed_parses;
+mod m20250901_000011_create_shorts_detection_cache;
+mod m20250901_000012_add_video_queue_video_id_unique;
+mod m20250901_000013_create_subscription_refresh_state;
+mod m20250901_000014_add_subscription_queue_scheduled_for;
+mod m20250901_000015_add_video_queue_channel_name;
+mod m20250901_000016_add_video_queue_sender_ip;
+mod m20250902_000001_add_indexes;
+mod m20250902_000002_add_known_channels_shorts_ratio;
+mod m20250902_000003_add_known_channels_notifications_paused;
+mod m20250902_000004_create_youtube_api_calls;
+mod m20250902_000005_add_subscription_queue_priority;
+mod m20250902_000006_add_video_queue_result_playlist_id;
+mod m20250902_000007_add_video_queue_result_shorts_detection_method;
+mod m20250902_000008_add_subscription_queue_source;
+mod m20250902_000009_add_video_queue_processing_since;
+mod m20250902_000010_add_known_channels_subscription_stats;
+mod m20250902_000011_create_video_blacklist;
+mod m20250902_000012_add_subscription_queue_retry_count;
+mod m20250902_000013_add_video_queue_result_playlist_item_id;
+mod m20250902_000014_add_video_queue_priority;
+mod m20250902_000015_create_maintenance_windows;
+mod m20250902_000016_add_video_queue_result_error_message;
+mod m20250902_000017_add_known_channels_metadata_updated_at;
+mod m20250902_000018_add_subscription_queue_result_skipped;
+mod m20250902_000019_add_video_queue_is_short_hint;
+mod m20250902_000020_add_video_queue_target_playlist_id;
+mod m20250902_000021_add_known_channels_profile_picture_updated_at;
+mod m20250902_000022_add_known_channels_shorts_ratio_samples;
+mod m20250902_000023_create_actor_runs;
+mod m20250902_000024_add_subscription_queue_confirmation;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20250901_000001_create_tables::Migration)]
+        vec![
+            Box::new(m20250901_000001_create_tables::Migration),
+            Box::new(m20250901_000002_create_audit_log::Migration),
+            Box::new(m20250901_000003_create_channel_groups::Migration),
+            Box::new(m20250901_000004_add_known_channels_is_shorts_channel::Migration),
+            Box::new(m20250901_000005_add_known_channels_video_age_threshold::Migration),
+            Box::new(m20250901_000006_add_known_channels_last_video_at::Migration),
+            Box::new(m20250901_000007_add_known_channels_handle::Migration),
+            Box::new(m20250901_000008_create_subscription_sync_runs::Migration),
+            Box::new(m20250901_000009_add_subscription_secrets::Migration),
+            Box::new(m20250901_000010_create_failed_feed_parses::Migration),
+            Box::new(m20250901_000011_create_shorts_detection_cache::Migration),
+            Box::new(m20250901_000012_add_video_queue_video_id_unique::Migration),
+            Box::new(m20250901_000013_create_subscription_refresh_state::Migration),
+            Box::new(m20250901_000014_add_subscription_queue_scheduled_for::Migration),
+            Box::new(m20250901_000015_add_video_queue_channel_name::Migration),
+            Box::new(m20250901_000016_add_video_queue_sender_ip::Migration),
+            Box::new(m20250902_000001_add_indexes::Migration),
+            Box::new(m20250902_000002_add_known_channels_shorts_ratio::Migration),
+            Box::new(m20250902_000003_add_known_channels_notifications_paused::Migration),
+            Box::new(m20250902_000004_create_youtube_api_calls::Migration),
+            Box::new(m20250902_000005_add_subscription_queue_priority::Migration),
+            Box::new(m20250902_000006_add_video_queue_result_playlist_id::Migration),
+            Box::new(m20250902_000007_add_video_queue_result_shorts_detection_method::Migration),
+            Box::new(m20250902_000008_add_subscription_queue_source::Migration),
+            Box::new(m20250902_000009_add_video_queue_processing_since::Migration),
+            Box::new(m20250902_000010_add_known_channels_subscription_stats::Migration),
+            Box::new(m20250902_000011_create_video_blacklist::Migration),
+            Box::new(m20250902_000012_add_subscription_queue_retry_count::Migration),
+            Box::new(m20250902_000013_add_video_queue_result_playlist_item_id::Migration),
+            Box::new(m20250902_000014_add_video_queue_priority::Migration),
+            Box::new(m20250902_000015_create_maintenance_windows::Migration),
+            Box::new(m20250902_000016_add_video_queue_result_error_message::Migration),
+            Box::new(m20250902_000017_add_known_channels_metadata_updated_at::Migration),
+            Box::new(m20250902_000018_add_subscription_queue_result_skipped::Migration),
+            Box::new(m20250902_000019_add_video_queue_is_short_hint::Migration),
+            Box::new(m20250902_000020_add_video_queue_target_playlist_id::Migration),
+            Box::new(m20250902_000021_add_known_channels_profile_picture_updated_at::Migration),
+            Box::new(m20250902_000022_add_known_channels_shorts_ratio_samples::Migration),
+            Box::new(m20250902_000023_create_actor_runs::Migration),
+            Box::new(m20250902_000024_add_subscription_queue_confirmation::Migration),
+        ]
     }
 }
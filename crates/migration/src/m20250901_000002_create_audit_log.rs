@@ -0,0 +1,46 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(
+                        schema::big_integer(AuditLog::Id)
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(schema::text(AuditLog::Action))
+                    .col(schema::text(AuditLog::Actor))
+                    .col(schema::text_null(AuditLog::Target))
+                    .col(schema::text_null(AuditLog::Details))
+                    .col(schema::big_integer(AuditLog::Timestamp))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditLog {
+    Table,
+    Id,
+
+    Action,
+    Actor,
+    Target,
+    Details,
+    Timestamp,
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChannelGroups::Table)
+                    .if_not_exists()
+                    .col(schema::pk_auto(ChannelGroups::Id))
+                    .col(schema::text_uniq(ChannelGroups::Name))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChannelGroupMembers::Table)
+                    .if_not_exists()
+                    .col(schema::integer(ChannelGroupMembers::GroupId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-channel_group_members-group_id")
+                            .from(ChannelGroupMembers::Table, ChannelGroupMembers::GroupId)
+                            .to(ChannelGroups::Table, ChannelGroups::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(schema::text(ChannelGroupMembers::ChannelId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-channel_group_members-channel_id")
+                            .from(ChannelGroupMembers::Table, ChannelGroupMembers::ChannelId)
+                            .to(KnownChannels::Table, KnownChannels::ChannelId),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ChannelGroupMembers::GroupId)
+                            .col(ChannelGroupMembers::ChannelId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ChannelGroupMembers::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ChannelGroups::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum KnownChannels {
+    Table,
+    ChannelId,
+}
+
+#[derive(DeriveIden)]
+enum ChannelGroups {
+    Table,
+    Id,
+
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum ChannelGroupMembers {
+    Table,
+    GroupId,
+    ChannelId,
+}
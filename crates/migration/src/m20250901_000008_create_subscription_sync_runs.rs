@@ -0,0 +1,48 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubscriptionSyncRuns::Table)
+                    .if_not_exists()
+                    .col(
+                        schema::big_integer(SubscriptionSyncRuns::Id)
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(schema::big_integer(SubscriptionSyncRuns::Timestamp))
+                    .col(schema::integer(SubscriptionSyncRuns::ChannelsAdded))
+                    .col(schema::integer(SubscriptionSyncRuns::ChannelsRemoved))
+                    .col(schema::integer(SubscriptionSyncRuns::ApiPages))
+                    .col(schema::big_integer(SubscriptionSyncRuns::DurationMs))
+                    .col(schema::boolean(SubscriptionSyncRuns::EtagHit))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubscriptionSyncRuns::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SubscriptionSyncRuns {
+    Table,
+    Id,
+
+    Timestamp,
+    ChannelsAdded,
+    ChannelsRemoved,
+    ApiPages,
+    DurationMs,
+    EtagHit,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ActiveSubscriptions::Table)
+                    .add_column(schema::text_null(ActiveSubscriptions::Secret))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscriptionQueue::Table)
+                    .add_column(schema::text_null(SubscriptionQueue::Secret))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscriptionQueue::Table)
+                    .drop_column(SubscriptionQueue::Secret)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ActiveSubscriptions::Table)
+                    .drop_column(ActiveSubscriptions::Secret)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ActiveSubscriptions {
+    Table,
+    Secret,
+}
+
+#[derive(DeriveIden)]
+enum SubscriptionQueue {
+    Table,
+    Secret,
+}
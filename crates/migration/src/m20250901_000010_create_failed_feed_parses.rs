@@ -0,0 +1,42 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FailedFeedParses::Table)
+                    .if_not_exists()
+                    .col(
+                        schema::big_integer(FailedFeedParses::Id)
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(schema::text(FailedFeedParses::Body))
+                    .col(schema::text(FailedFeedParses::Error))
+                    .col(schema::big_integer(FailedFeedParses::Timestamp))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FailedFeedParses::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum FailedFeedParses {
+    Table,
+    Id,
+
+    Body,
+    Error,
+    Timestamp,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShortsDetectionCache::Table)
+                    .if_not_exists()
+                    .col(schema::text(ShortsDetectionCache::VideoId).primary_key())
+                    .col(schema::boolean(ShortsDetectionCache::IsShort))
+                    .col(schema::text(ShortsDetectionCache::DetectionMethod))
+                    .col(schema::big_integer(ShortsDetectionCache::DetectedAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ShortsDetectionCache::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ShortsDetectionCache {
+    Table,
+    VideoId,
+
+    IsShort,
+    DetectionMethod,
+    DetectedAt,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Keep only the first queued copy of each video, along with its result, so that the
+        // unique index can be created
+        let first_ids = Query::select()
+            .expr(Expr::col(VideoQueue::Id).min())
+            .from(VideoQueue::Table)
+            .group_by_col(VideoQueue::VideoId)
+            .to_owned();
+
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(VideoQueueResult::Table)
+                    .and_where(
+                        Expr::col(VideoQueueResult::QueueId).not_in_subquery(first_ids.clone()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(VideoQueue::Table)
+                    .and_where(Expr::col(VideoQueue::Id).not_in_subquery(first_ids))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-video_queue-video_id")
+                    .table(VideoQueue::Table)
+                    .col(VideoQueue::VideoId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-video_queue-video_id")
+                    .table(VideoQueue::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VideoQueue {
+    Table,
+    Id,
+    VideoId,
+}
+
+#[derive(DeriveIden)]
+enum VideoQueueResult {
+    Table,
+    QueueId,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubscriptionRefreshState::Table)
+                    .if_not_exists()
+                    .col(schema::integer(SubscriptionRefreshState::Id).primary_key())
+                    .col(schema::big_integer(SubscriptionRefreshState::NextWakeupAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(SubscriptionRefreshState::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SubscriptionRefreshState {
+    Table,
+    Id,
+
+    NextWakeupAt,
+}
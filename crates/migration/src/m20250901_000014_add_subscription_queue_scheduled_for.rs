@@ -0,0 +1,35 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscriptionQueue::Table)
+                    .add_column(schema::big_integer_null(SubscriptionQueue::ScheduledFor))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscriptionQueue::Table)
+                    .drop_column(SubscriptionQueue::ScheduledFor)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SubscriptionQueue {
+    Table,
+    ScheduledFor,
+}
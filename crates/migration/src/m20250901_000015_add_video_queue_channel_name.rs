@@ -0,0 +1,76 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(VideoQueue::Table)
+                    .add_column(schema::text(VideoQueue::ChannelName).default(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Backfill the names of the channels of existing videos
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(VideoQueue::Table)
+                    .value(
+                        VideoQueue::ChannelName,
+                        // Videos from channels that are no longer known keep an empty name
+                        Func::coalesce([
+                            SimpleExpr::SubQuery(
+                                None,
+                                Box::new(
+                                    Query::select()
+                                        .column(KnownChannels::ChannelName)
+                                        .from(KnownChannels::Table)
+                                        .and_where(
+                                            Expr::col((
+                                                KnownChannels::Table,
+                                                KnownChannels::ChannelId,
+                                            ))
+                                            .equals((VideoQueue::Table, VideoQueue::ChannelId)),
+                                        )
+                                        .to_owned()
+                                        .into_sub_query_statement(),
+                                ),
+                            ),
+                            Expr::val("").into(),
+                        ]),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(VideoQueue::Table)
+                    .drop_column(VideoQueue::ChannelName)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VideoQueue {
+    Table,
+    ChannelId,
+    ChannelName,
+}
+
+#[derive(DeriveIden)]
+enum KnownChannels {
+    Table,
+    ChannelId,
+    ChannelName,
+}
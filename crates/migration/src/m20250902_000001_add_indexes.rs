@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // `video_queue(video_id)` is already covered by its unique index
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-subscription_queue-channel_id")
+                    .table(SubscriptionQueue::Table)
+                    .col(SubscriptionQueue::ChannelId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-subscription_queue-channel_id")
+                    .table(SubscriptionQueue::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SubscriptionQueue {
+    Table,
+    ChannelId,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(YoutubeApiCalls::Table)
+                    .if_not_exists()
+                    .col(
+                        schema::big_integer(YoutubeApiCalls::Id)
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(schema::text(YoutubeApiCalls::Endpoint))
+                    .col(schema::text_null(YoutubeApiCalls::VideoId))
+                    .col(schema::text_null(YoutubeApiCalls::ChannelId))
+                    .col(schema::integer(YoutubeApiCalls::QuotaCost))
+                    .col(schema::integer_null(YoutubeApiCalls::StatusCode))
+                    .col(schema::big_integer(YoutubeApiCalls::Timestamp))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-youtube_api_calls-timestamp")
+                    .table(YoutubeApiCalls::Table)
+                    .col(YoutubeApiCalls::Timestamp)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(YoutubeApiCalls::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum YoutubeApiCalls {
+    Table,
+    Id,
+
+    Endpoint,
+    VideoId,
+    ChannelId,
+    QuotaCost,
+    StatusCode,
+    Timestamp,
+}
@@ -0,0 +1,51 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscriptionQueue::Table)
+                    .add_column(schema::integer(SubscriptionQueue::Priority).default(5))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Backfill the priorities of existing actions
+        for (action, priority) in [("unsubscribe", 1), ("refresh", 10)] {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(SubscriptionQueue::Table)
+                        .value(SubscriptionQueue::Priority, priority)
+                        .and_where(Expr::col(SubscriptionQueue::Action).eq(action))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscriptionQueue::Table)
+                    .drop_column(SubscriptionQueue::Priority)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SubscriptionQueue {
+    Table,
+    Action,
+    Priority,
+}
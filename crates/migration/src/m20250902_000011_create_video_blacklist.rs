@@ -0,0 +1,38 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VideoBlacklist::Table)
+                    .if_not_exists()
+                    .col(schema::text(VideoBlacklist::VideoId).primary_key())
+                    .col(schema::text(VideoBlacklist::Reason))
+                    .col(schema::text(VideoBlacklist::Actor))
+                    .col(schema::big_integer(VideoBlacklist::Timestamp))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VideoBlacklist::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VideoBlacklist {
+    Table,
+    VideoId,
+
+    Reason,
+    Actor,
+    Timestamp,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MaintenanceWindows::Table)
+                    .if_not_exists()
+                    .col(
+                        schema::big_integer(MaintenanceWindows::Id)
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(schema::big_integer(MaintenanceWindows::StartsAt))
+                    .col(schema::big_integer(MaintenanceWindows::EndsAt))
+                    .col(schema::text(MaintenanceWindows::Reason))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MaintenanceWindows::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MaintenanceWindows {
+    Table,
+    Id,
+
+    StartsAt,
+    EndsAt,
+    Reason,
+}
@@ -0,0 +1,33 @@
+use sea_orm_migration::{prelude::*, schema};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ActorRuns::Table)
+                    .if_not_exists()
+                    .col(schema::text(ActorRuns::Name).primary_key())
+                    .col(schema::big_integer(ActorRuns::LastRunAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ActorRuns::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ActorRuns {
+    Table,
+    Name,
+    LastRunAt,
+}
@@ -1,40 +1,80 @@
-use std::env;
+use std::{env, iter::Peekable};
 
-use color_eyre::eyre::Context;
+use color_eyre::eyre::{Context, bail};
 use lexopt::ValueExt;
 use migration::Migrator;
 use sea_orm_migration::prelude::*;
 
+mod status;
+
+const USAGE: &str =
+    "usage: migration [--json] <fresh | refresh | reset | status | up [count] | down <count>>...";
+
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
-    tracing_subscriber::fmt().init();
+    // Logs go to stderr so that the status output can be parsed
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
 
     let database_url = env::var("DATABASE_URL").wrap_err("DATABASE_URL should be set")?;
 
     let connection = sea_orm::Database::connect(&database_url).await?;
 
+    let mut json = false;
+    let mut commands = Vec::new();
+
     let mut arg_parser = lexopt::Parser::from_env();
     while let Some(argument) = arg_parser.next().wrap_err("failed to parse arguments")? {
         match argument {
-            lexopt::Arg::Short(_) => todo!(),
-            lexopt::Arg::Long(_) => todo!(),
+            lexopt::Arg::Long("json") => json = true,
+            lexopt::Arg::Short(_) | lexopt::Arg::Long(_) => {
+                return Err(argument.unexpected()).wrap_err(USAGE);
+            }
             lexopt::Arg::Value(os_string) => {
-                match os_string
-                    .string()
-                    .wrap_err("invalid utf-8 in arguments")?
-                    .as_str()
-                {
-                    "fresh" => Migrator::fresh(&connection).await?,
-                    "refresh" => Migrator::refresh(&connection).await?,
-                    "reset" => Migrator::reset(&connection).await?,
-                    "status" => Migrator::status(&connection).await?,
-                    "up" => unimplemented!("requires second argument"),
-                    "down" => unimplemented!("requires second argument"),
-                    _ => unimplemented!(),
+                commands.push(os_string.string().wrap_err("invalid utf-8 in arguments")?)
+            }
+        }
+    }
+
+    if commands.is_empty() {
+        bail!("no command given\n{USAGE}");
+    }
+
+    let mut commands = commands.into_iter().peekable();
+
+    while let Some(command) = commands.next() {
+        match command.as_str() {
+            "fresh" => Migrator::fresh(&connection).await?,
+            "refresh" => Migrator::refresh(&connection).await?,
+            "reset" => Migrator::reset(&connection).await?,
+            "status" => {
+                let rows = status::migration_status(&connection).await?;
+
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&rows)?);
+                } else {
+                    println!("{}", status::format_table(&rows));
                 }
             }
+            // Applies every pending migration without a count
+            "up" => Migrator::up(&connection, count(&mut commands)).await?,
+            // Rolling back every migration needs to be asked for with `reset` instead
+            "down" => match count(&mut commands) {
+                Some(count) => Migrator::down(&connection, Some(count)).await?,
+                None => bail!("down needs the number of migrations to roll back\n{USAGE}"),
+            },
+            command => bail!("unknown command {command:?}\n{USAGE}"),
         }
     }
 
     Ok(())
 }
+
+/// Take the optional number of migrations that follows `up` or `down`
+fn count(commands: &mut Peekable<impl Iterator<Item = String>>) -> Option<u32> {
+    let count = commands.peek()?.parse().ok()?;
+    commands.next();
+
+    Some(count)
+}
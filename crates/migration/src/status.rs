@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use comfy_table::Table;
+use jiff::{SignedDuration, Timestamp};
+use migration::Migrator;
+use sea_orm::DatabaseConnection;
+use sea_orm_migration::{MigratorTrait as _, prelude::*};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct MigrationRow {
+    name: String,
+    status: String,
+    /// Missing for pending migrations
+    applied_at: Option<Timestamp>,
+    /// Time between this and the previously applied migration, which is how long this one took
+    /// if they were applied together. Only second precision is recorded
+    duration: Option<SignedDuration>,
+}
+
+/// Status of every known migration, in the order that they are applied
+pub async fn migration_status(connection: &DatabaseConnection) -> Result<Vec<MigrationRow>, DbErr> {
+    let applied_at = Migrator::get_migration_models(connection)
+        .await?
+        .into_iter()
+        .map(|model| (model.version, model.applied_at))
+        .collect::<HashMap<_, _>>();
+
+    let mut previous_applied_at: Option<Timestamp> = None;
+
+    Ok(Migrator::get_migration_with_status(connection)
+        .await?
+        .into_iter()
+        .map(|migration| {
+            let applied_at = applied_at
+                .get(migration.name())
+                .and_then(|&seconds| Timestamp::from_second(seconds).ok());
+            let duration = previous_applied_at
+                .zip(applied_at)
+                .map(|(previous, applied_at)| previous.duration_until(applied_at));
+
+            if applied_at.is_some() {
+                previous_applied_at = applied_at;
+            }
+
+            MigrationRow {
+                name: migration.name().to_owned(),
+                status: migration.status().to_string(),
+                applied_at,
+                duration,
+            }
+        })
+        .collect())
+}
+
+/// Format the migration status as a table for humans to read
+pub fn format_table(rows: &[MigrationRow]) -> Table {
+    let mut table = Table::new();
+
+    table
+        .set_header(["Name", "Status", "Applied At", "Duration"])
+        .add_rows(rows.iter().map(|row| {
+            [
+                row.name.clone(),
+                row.status.clone(),
+                row.applied_at
+                    .map(|applied_at| applied_at.to_string())
+                    .unwrap_or_else(|| "-".to_owned()),
+                row.duration
+                    .map(|duration| format!("{duration:#}"))
+                    .unwrap_or_else(|| "-".to_owned()),
+            ]
+        }));
+
+    table
+}